@@ -0,0 +1,121 @@
+//! Renders the same scene in four viewports, each camera having its own blur settings.
+//!
+//! Each quarter of the window must show a different blur strength:
+//! the settings of a camera never leak into the viewports of the other cameras.
+use bevy::{
+    prelude::*,
+    render::camera::{ClearColorConfig, Viewport},
+    sprite::MaterialMesh2dBundle,
+    window::WindowResized,
+};
+use bevy_camera_blur::*;
+
+mod helpers;
+
+fn main() {
+    helpers::common_app()
+        .add_plugins((GaussianBlurPlugin, DualBlurPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, set_camera_viewports)
+        .run();
+}
+
+// Position of the viewport of a camera in the 2x2 grid.
+#[derive(Component)]
+struct CameraPosition(UVec2);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Sharp edges and thin lines make the strength of each blur visible.
+    for (i, color) in [Color::PURPLE, Color::ORANGE, Color::CYAN]
+        .into_iter()
+        .enumerate()
+    {
+        commands.spawn(MaterialMesh2dBundle {
+            mesh: meshes
+                .add(Rectangle::new(160.0, 8.0 + 24.0 * i as f32))
+                .into(),
+            transform: Transform::from_xyz(0.0, 60.0 * (i as f32 - 1.0), 0.0),
+            material: materials.add(ColorMaterial::from(color)),
+            ..default()
+        });
+    }
+
+    let cameras = [
+        (UVec2::new(0, 0), "Gaussian, sigma 2"),
+        (UVec2::new(1, 0), "Gaussian, sigma 12"),
+        (UVec2::new(0, 1), "Dual, sigma 2"),
+        (UVec2::new(1, 1), "Dual, sigma 12"),
+    ];
+    for (index, (position, label)) in cameras.into_iter().enumerate() {
+        let mut camera = commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: index as isize,
+                    // Only the first camera clears the window, the others draw in their viewport.
+                    clear_color: if index == 0 {
+                        ClearColorConfig::Default
+                    } else {
+                        ClearColorConfig::None
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            CameraPosition(position),
+        ));
+        let sigma = if position.x == 0 { 2.0 } else { 12.0 };
+        if position.y == 0 {
+            camera.insert(GaussianBlurSettings::from_sigma(sigma));
+        } else {
+            camera.insert(DualBlurSettings::from_sigma(sigma));
+        }
+        let camera = camera.id();
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                TargetCamera(camera),
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+fn set_camera_viewports(
+    windows: Query<&Window>,
+    mut resize_events: EventReader<WindowResized>,
+    mut cameras: Query<(&CameraPosition, &mut Camera)>,
+) {
+    // The viewports are set again when the window is resized, the first resize event being sent at startup.
+    for resize_event in resize_events.read() {
+        let window = windows.get(resize_event.window).unwrap();
+        let size = UVec2::new(window.physical_width(), window.physical_height()) / 2;
+        for (position, mut camera) in &mut cameras {
+            camera.viewport = Some(Viewport {
+                physical_position: position.0 * size,
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
@@ -12,12 +12,14 @@ pub fn common_animation_app() -> App {
     app.add_plugins((
         GaussianBlurPlugin,
         BoxBlurPlugin,
+        KawaseBlurPlugin,
         DualBlurPlugin,
         TweeningPlugin,
     ))
     .init_state::<BlurType>()
     .insert_resource(ResComp::<GaussianBlurSettings>::default())
     .insert_resource(ResComp::<BoxBlurSettings>::default())
+    .insert_resource(ResComp::<KawaseBlurSettings>::default())
     .insert_resource(ResComp::<DualBlurSettings>::default())
     .init_state::<GameState>()
     .insert_resource(AnimationDurationMs(500))
@@ -34,14 +36,19 @@ pub fn common_animation_app() -> App {
         (
             component_animator_system::<BoxBlurSettings>,
             component_animator_system::<GaussianBlurSettings>,
+            component_animator_system::<KawaseBlurSettings>,
+            component_animator_system::<DualBlurSettings>,
             gamestate_interaction,
             blur_settings_color::<GaussianBlurSettings>.run_if(in_state(BlurType::Gaussian)),
             blur_settings_color::<BoxBlurSettings>.run_if(in_state(BlurType::Box)),
+            blur_settings_color::<KawaseBlurSettings>.run_if(in_state(BlurType::Kawase)),
             blur_settings_color::<DualBlurSettings>.run_if(in_state(BlurType::Dual)),
             update_gaussian_blur_settings
                 .run_if(in_state(BlurType::Gaussian).and_then(in_state(GameState::Menu))),
             update_box_blur_settings
                 .run_if(in_state(BlurType::Box).and_then(in_state(GameState::Menu))),
+            update_kawase_blur_settings
+                .run_if(in_state(BlurType::Kawase).and_then(in_state(GameState::Menu))),
             update_dual_blur_settings
                 .run_if(in_state(BlurType::Dual).and_then(in_state(GameState::Menu))),
             update_blurtype,
@@ -55,6 +62,8 @@ pub fn common_animation_app() -> App {
             animate_blur::<BoxBlurSettings, BoxBlurLens>.run_if(in_state(BlurType::Box)),
             animate_blur::<GaussianBlurSettings, GaussianBlurLens>
                 .run_if(in_state(BlurType::Gaussian)),
+            animate_blur::<KawaseBlurSettings, KawaseBlurLens>.run_if(in_state(BlurType::Kawase)),
+            animate_blur::<DualBlurSettings, DualBlurLens>.run_if(in_state(BlurType::Dual)),
         ),
     )
     .add_systems(
@@ -64,6 +73,8 @@ pub fn common_animation_app() -> App {
             animate_deblur::<BoxBlurSettings, BoxBlurLens>.run_if(in_state(BlurType::Box)),
             animate_deblur::<GaussianBlurSettings, GaussianBlurLens>
                 .run_if(in_state(BlurType::Gaussian)),
+            animate_deblur::<KawaseBlurSettings, KawaseBlurLens>.run_if(in_state(BlurType::Kawase)),
+            animate_deblur::<DualBlurSettings, DualBlurLens>.run_if(in_state(BlurType::Dual)),
         ),
     )
     .add_systems(
@@ -73,6 +84,8 @@ pub fn common_animation_app() -> App {
     .add_systems(OnExit(BlurType::Gaussian), del_blur::<GaussianBlurSettings>)
     .add_systems(OnEnter(BlurType::Box), add_blur::<BoxBlurSettings>)
     .add_systems(OnExit(BlurType::Box), del_blur::<BoxBlurSettings>)
+    .add_systems(OnEnter(BlurType::Kawase), add_blur::<KawaseBlurSettings>)
+    .add_systems(OnExit(BlurType::Kawase), del_blur::<KawaseBlurSettings>)
     .add_systems(OnEnter(BlurType::Dual), add_blur::<DualBlurSettings>)
     .add_systems(OnExit(BlurType::Dual), del_blur::<DualBlurSettings>);
     app
@@ -84,36 +97,44 @@ enum GameState {
     Menu,
     Game,
 }
-const ANIMATABLE_BLURTYPES: [BlurType; 3] = [BlurType::Gaussian, BlurType::Box, BlurType::Dual];
+const ANIMATABLE_BLURTYPES: [BlurType; 4] = [
+    BlurType::Gaussian,
+    BlurType::Box,
+    BlurType::Kawase,
+    BlurType::Dual,
+];
 
 fn update_blurtype(
     state: Res<State<BlurType>>,
     input: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<BlurType>>,
 ) {
-    if input.just_pressed(KeyCode::Left) {
+    if input.just_pressed(KeyCode::ArrowLeft) {
         let mut new = state.prev();
         while !ANIMATABLE_BLURTYPES.contains(&new) {
             new = new.prev();
         }
         next_state.set(new);
     }
-    if input.just_pressed(KeyCode::Right) {
+    if input.just_pressed(KeyCode::ArrowRight) {
         let mut new = state.next();
         while !ANIMATABLE_BLURTYPES.contains(&new) {
             new = new.next();
         }
         next_state.set(new);
     }
-    if input.just_pressed(KeyCode::Key0) {
+    if input.just_pressed(KeyCode::Digit1) {
         next_state.set(ANIMATABLE_BLURTYPES[0]);
     }
-    if input.just_pressed(KeyCode::Key1) {
+    if input.just_pressed(KeyCode::Digit2) {
         next_state.set(ANIMATABLE_BLURTYPES[1]);
     }
-    if input.just_pressed(KeyCode::Key2) {
+    if input.just_pressed(KeyCode::Digit3) {
         next_state.set(ANIMATABLE_BLURTYPES[2]);
     }
+    if input.just_pressed(KeyCode::Digit4) {
+        next_state.set(ANIMATABLE_BLURTYPES[3]);
+    }
 }
 
 fn gamestate_interaction(
@@ -139,10 +160,10 @@ fn gamestate_interaction(
             GameState::Game => GameState::Menu,
         })
     }
-    if input.just_pressed(KeyCode::Up) {
+    if input.just_pressed(KeyCode::ArrowUp) {
         duration.0 += 100;
     }
-    if input.just_pressed(KeyCode::Down) {
+    if input.just_pressed(KeyCode::ArrowDown) {
         duration.0 = duration.0.max(100) - 100;
     }
 }
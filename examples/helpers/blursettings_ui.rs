@@ -33,7 +33,8 @@ pub fn setup_blur_settings_ui(mut commands: Commands) {
 
 fn settings_info(settings: &impl BlurSetting) -> String {
     format!(
-        "\nSetting cost:\nSamplings per pixel: ~{:.1}\nPost-processing passes: {}",
+        "\nEquivalent sigma: {:.1}\nSetting cost:\nSamplings per pixel: ~{:.1}\nPost-processing passes: {}",
+        settings.equivalent_sigma(),
         settings.sampling_per_pixel(),
         settings.passes(),
     )
@@ -50,17 +51,29 @@ pub fn update_gaussian_blur_settings(
         let text = &mut text.sections[0].value;
 
         *text = "Gaussian Blur settings:\n".to_string();
-        text.push_str(&format!("(Q/A) Kernel size: {}\n", settings.kernel_size));
+        text.push_str(&format!("(Q/A) Sigma: {:.2}\n", settings.sigma));
+        text.push_str(&format!(
+            "(E/D) Truncation: {:.0} sigma, Kernel size: {}\n",
+            settings.truncation,
+            settings.concrete_kernel_size()
+        ));
         text.push_str(&format!(
             "(W/S) Sampling distance factor: {:.1}\n",
             settings.sampling_distance_factor
         ));
+        text.push_str(&format!("(R) Backend: {:?}\n", settings.backend));
 
-        if keycode.just_pressed(KeyCode::KeyA) {
-            settings.kernel_size = settings.kernel_size.saturating_sub(2).clamp(1, 401);
+        if keycode.pressed(KeyCode::KeyQ) {
+            settings.sigma = (settings.sigma + time.delta_seconds() * 5.).clamp(0., 100.);
         }
-        if keycode.just_pressed(KeyCode::KeyQ) {
-            settings.kernel_size = (settings.kernel_size + 2).clamp(1, 401);
+        if keycode.pressed(KeyCode::KeyA) {
+            settings.sigma = (settings.sigma - time.delta_seconds() * 5.).clamp(0., 100.);
+        }
+        if keycode.just_pressed(KeyCode::KeyE) {
+            settings.truncation = (settings.truncation + 1.).clamp(1., 6.);
+        }
+        if keycode.just_pressed(KeyCode::KeyD) {
+            settings.truncation = (settings.truncation - 1.).clamp(1., 6.);
         }
         if keycode.pressed(KeyCode::KeyW) {
             settings.sampling_distance_factor =
@@ -70,6 +83,12 @@ pub fn update_gaussian_blur_settings(
             settings.sampling_distance_factor =
                 (settings.sampling_distance_factor - time.delta_seconds() * 1.).clamp(1., 100.);
         }
+        if keycode.just_pressed(KeyCode::KeyR) {
+            settings.backend = match settings.backend {
+                GaussianBlurBackend::Fragment => GaussianBlurBackend::Compute,
+                GaussianBlurBackend::Compute => GaussianBlurBackend::Fragment,
+            };
+        }
     }
 }
 
@@ -86,6 +105,14 @@ pub fn update_box_blur_settings(
         *text = "Box Blur settings:\n".to_string();
         text.push_str(&format!("(Q/A) Kernel size: {}\n", settings.kernel_size));
         text.push_str(&format!("(W/S) passes: {:?}\n", settings.passes));
+        text.push_str(&format!(
+            "(E/D) Sampling distance factor: {:.0}\n",
+            settings.sampling_distance_factor
+        ));
+        text.push_str(&format!(
+            "(R) Per-pass kernel sizes: {:?}\n",
+            settings.kernel_sizes
+        ));
 
         if settings_ui.allow_user_interaction {
             if keycode.just_pressed(KeyCode::KeyA) {
@@ -103,6 +130,25 @@ pub fn update_box_blur_settings(
                 settings.passes += 1;
             }
             settings.passes = settings.passes.clamp(1, 5);
+
+            if keycode.just_pressed(KeyCode::KeyE) {
+                settings.sampling_distance_factor += 1.;
+            }
+            if keycode.just_pressed(KeyCode::KeyD) {
+                settings.sampling_distance_factor -= 1.;
+            }
+            settings.sampling_distance_factor = settings.sampling_distance_factor.clamp(1., 100.);
+
+            // Switches between a single kernel size and the per-pass kernel sizes of the same blur.
+            if keycode.just_pressed(KeyCode::KeyR) {
+                settings.kernel_sizes = if settings.kernel_sizes.is_empty() {
+                    let sigma = settings.equivalent_sigma() / settings.sampling_distance_factor;
+                    BoxBlurSettings::from_sigma_with_kernel_sizes(sigma, settings.passes)
+                        .kernel_sizes
+                } else {
+                    vec![]
+                };
+            }
         }
     }
 }
@@ -141,7 +187,7 @@ pub fn update_kawase_blur_settings(
         text.push_str("(D/F) Change selected kernel entry\n");
 
         if keycode.just_pressed(KeyCode::KeyQ) {
-            let v = settings.sampling_distances.len() as u32;
+            let v = settings.sampling_distances.len() as f32;
             settings.sampling_distances.push(v);
         }
         if keycode.just_pressed(KeyCode::KeyA) {
@@ -155,11 +201,11 @@ pub fn update_kawase_blur_settings(
             *selected = (*selected + 1).min(settings.sampling_distances.len() - 1);
         }
         if keycode.just_pressed(KeyCode::KeyW) {
-            settings.sampling_distances[*selected] += 1;
+            settings.sampling_distances[*selected] += 1.0;
         }
         if keycode.just_pressed(KeyCode::KeyS) {
             settings.sampling_distances[*selected] =
-                settings.sampling_distances[*selected].max(1) - 1;
+                (settings.sampling_distances[*selected] - 1.0).max(0.0);
         }
     }
 }
@@ -168,6 +214,7 @@ pub fn update_dual_blur_settings(
     mut settings: Query<&mut DualBlurSettings, With<Camera>>,
     mut text: Query<&mut Text, With<BlurSettingsUiText>>,
     keycode: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
 ) {
     if let Ok(mut settings) = settings.get_single_mut() {
         let mut text = text.single_mut();
@@ -175,16 +222,30 @@ pub fn update_dual_blur_settings(
         let text = &mut text.sections[0].value;
 
         *text = "Dual Blur settings:\n".to_string();
+        text.push_str(&format!("(Q/A) Strength: {:.2}\n", settings.strength));
+        text.push_str(&format!("(W/S) Offset: {:.1}\n", settings.offset));
         text.push_str(&format!(
-            "(Q/A) Downsampling passes: {}\n",
-            settings.downsampling_passes
+            "(E/D) Scale factor: {:.2}\n",
+            settings.scale_factor
         ));
 
-        if keycode.just_pressed(KeyCode::KeyQ) {
-            settings.downsampling_passes += 1
+        if keycode.pressed(KeyCode::KeyQ) {
+            settings.strength = (settings.strength + time.delta_seconds()).clamp(0., 8.);
         }
-        if keycode.just_pressed(KeyCode::KeyA) {
-            settings.downsampling_passes = settings.downsampling_passes.max(1) - 1;
+        if keycode.pressed(KeyCode::KeyA) {
+            settings.strength = (settings.strength - time.delta_seconds()).clamp(0., 8.);
+        }
+        if keycode.pressed(KeyCode::KeyW) {
+            settings.offset = (settings.offset + time.delta_seconds()).clamp(0., 8.);
+        }
+        if keycode.pressed(KeyCode::KeyS) {
+            settings.offset = (settings.offset - time.delta_seconds()).clamp(0., 8.);
+        }
+        if keycode.just_pressed(KeyCode::KeyE) {
+            settings.scale_factor = (settings.scale_factor + 0.25).clamp(1.25, 4.);
+        }
+        if keycode.just_pressed(KeyCode::KeyD) {
+            settings.scale_factor = (settings.scale_factor - 0.25).clamp(1.25, 4.);
         }
     }
 }
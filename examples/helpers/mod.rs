@@ -15,7 +15,7 @@ use bevy_camera_blur::*;
 mod blursettings_ui;
 use blursettings_ui::*;
 
-//pub mod animation;
+pub mod animation;
 pub mod showcase;
 
 pub fn common_app() -> App {
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+/// Accessibility preferences of the player, honored by all the blurs whatever the settings of the cameras,
+/// for example for players getting motion sickness from strong or animated blurs.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///fn reduce_blur(mut commands: Commands) {
+///    commands.insert_resource(BlurAccessibility {
+///        max_strength: Some(4.0),
+///        disable_animated: true,
+///        ..default()
+///    });
+///}
+///```
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Resource, Default)]
+pub struct BlurAccessibility {
+    /// Highest equivalent sigma, in physical pixels, of the blurs, see [`BlurSetting::equivalent_sigma`](crate::BlurSetting::equivalent_sigma).
+    /// - Stronger blurs are scaled down when extracted, as [`BlurUnits`](crate::BlurUnits) scale them.
+    /// - The radius of a variable blur is capped to twice this value.
+    /// - A value of 0 disables all the blurs.
+    /// - Defaults to `None`, not capping the blurs.
+    pub max_strength: Option<f32>,
+    /// When `true`, the [`BlurTransition`](crate::BlurTransition)s jump to their end, and are finished at once.
+    /// - Defaults to `false`
+    pub disable_animated: bool,
+    /// When `true`, no blur is rendered.
+    /// - Defaults to `false`
+    pub disable_all: bool,
+}
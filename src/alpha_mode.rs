@@ -0,0 +1,51 @@
+use bevy::{asset::load_internal_asset, prelude::*};
+
+const ALPHA_MODE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x3f6e92b1c4d84a0e8b7d5c21a9e04f36);
+
+/// Defines how a blur handles the alpha channel of the view, for cameras rendering to transparent targets.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlurAlphaMode {
+    /// The color channels and the alpha channel are averaged independently.
+    /// The colors of transparent pixels bleed on their neighbours, giving dark fringes around objects
+    /// rendered over a transparent clear color.
+    #[default]
+    Straight,
+    /// The colors are premultiplied by their alpha before being averaged, and unpremultiplied after,
+    /// so that transparent pixels do not contribute to the blurred colors.
+    Premultiplied,
+    /// The colors are blurred, but each pixel keeps its original alpha.
+    ///
+    /// With the dual blur, the alpha is kept by each downsampling level, the original alpha
+    /// being approximated at the view resolution.
+    PreserveAlpha,
+    /// Only the alpha is blurred, each pixel keeping its original colors, for example for soft shadows.
+    ///
+    /// With the dual blur, the colors are kept by each downsampling level, the original colors
+    /// being approximated at the view resolution.
+    AlphaOnly,
+}
+
+impl BlurAlphaMode {
+    // Value of the `alpha_mode` field of the uniforms, as expected by the `bevy_camera_blur::alpha_mode` WGSL module.
+    pub(crate) fn shader_value(self) -> u32 {
+        match self {
+            BlurAlphaMode::Straight => 0,
+            BlurAlphaMode::Premultiplied => 1,
+            BlurAlphaMode::PreserveAlpha => 2,
+            BlurAlphaMode::AlphaOnly => 3,
+        }
+    }
+}
+
+// Loads the `bevy_camera_blur::alpha_mode` WGSL module imported by the blur shaders.
+// Each blur plugin loads it, so that they can be added independently.
+pub(crate) fn load_alpha_mode_shader(app: &mut App) {
+    load_internal_asset!(
+        app,
+        ALPHA_MODE_SHADER_HANDLE,
+        "alpha_mode.wgsl",
+        Shader::from_wgsl
+    );
+    app.register_type::<BlurAlphaMode>();
+}
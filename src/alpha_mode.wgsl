@@ -0,0 +1,44 @@
+// Functions handling the alpha channel of a blur pass according to the `BlurAlphaMode` of the settings.
+//
+// Each pass averages the samples converted by `alpha_premultiply`, then converts the average to its output
+// with `alpha_finish`. Every pass reads and writes straight colors, so that the passes can be chained.
+
+#define_import_path bevy_camera_blur::alpha_mode
+
+const ALPHA_MODE_STRAIGHT: u32 = 0u;
+const ALPHA_MODE_PREMULTIPLIED: u32 = 1u;
+const ALPHA_MODE_PRESERVE_ALPHA: u32 = 2u;
+const ALPHA_MODE_ALPHA_ONLY: u32 = 3u;
+
+// Converts a sample to the color averaged by the blur, premultiplied by its alpha with the premultiplied mode.
+fn alpha_premultiply(color: vec4<f32>, alpha_mode: u32) -> vec4<f32> {
+    if alpha_mode == ALPHA_MODE_PREMULTIPLIED {
+        return vec4(color.rgb * color.a, color.a);
+    }
+    return color;
+}
+
+// Converts the average 'blurred' to the output of the pass for the pixel at 'uv' of 'texture'.
+//
+// The original pixel is only sampled by the modes keeping some of its channels.
+fn alpha_finish(
+    blurred: vec4<f32>,
+    texture: texture_2d<f32>,
+    texture_sampler: sampler,
+    uv: vec2<f32>,
+    alpha_mode: u32,
+) -> vec4<f32> {
+    if alpha_mode == ALPHA_MODE_PREMULTIPLIED {
+        if blurred.a > 0.0 {
+            return vec4(blurred.rgb / blurred.a, blurred.a);
+        }
+        return vec4(0.0);
+    } else if alpha_mode == ALPHA_MODE_PRESERVE_ALPHA {
+        let original = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+        return vec4(blurred.rgb, original.a);
+    } else if alpha_mode == ALPHA_MODE_ALPHA_ONLY {
+        let original = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+        return vec4(original.rgb, blurred.a);
+    }
+    return blurred;
+}
@@ -0,0 +1,598 @@
+use bevy::math::{IVec2, UVec2, Vec2};
+
+use crate::{
+    kawase_blur::kawase_pass_variance, BlurPlan, BlurSetting, BoxBlurSettings, DualBlurSettings,
+    GaussianBlurSettings, KawaseBlurSettings,
+};
+
+/// Computes on the CPU the effective impulse response of a blur setting,
+/// to compare its quality against an ideal gaussian blur.
+///
+/// The impulse response is computed by emulating each post-processing pass of the algorithm,
+/// with the same texture sizes, sampling positions and filtering as the shaders,
+/// on a single channel image containing only one lit pixel.
+///
+/// ```
+///# use bevy_camera_blur::*;
+/// let gaussian = GaussianBlurSettings::default().quality();
+/// let box_blur = BoxBlurSettings::from_sigma(5.0).quality();
+/// assert!(gaussian.psnr > box_blur.psnr);
+/// assert!(gaussian.anisotropy < box_blur.anisotropy);
+/// ```
+pub trait BlurAnalysis: BlurSetting {
+    /// Computes the impulse response of this setting.
+    fn impulse_response(&self) -> ImpulseResponse;
+
+    /// Compares the impulse response of this setting with the one
+    /// of a gaussian blur of the same [equivalent sigma](BlurSetting::equivalent_sigma).
+    fn quality(&self) -> BlurQualityMetrics {
+        self.impulse_response()
+            .compare_to_gaussian(self.equivalent_sigma())
+    }
+}
+
+/// Quality metrics of a blur setting compared to an ideal gaussian blur,
+/// see [`ImpulseResponse::compare_to_gaussian`].
+#[derive(Clone, Copy, Debug)]
+pub struct BlurQualityMetrics {
+    /// Sigma of the ideal gaussian blur used as reference.
+    pub sigma: f32,
+    /// Root mean square of the difference between the impulse response and the ideal gaussian kernel.
+    pub rms_error: f32,
+    /// Peak signal to noise ratio, in dB, of the impulse response,
+    /// the peak value being the center of the ideal gaussian kernel.
+    pub psnr: f32,
+    /// Relative difference between the blur extent along the axes and along the diagonals,
+    /// measured where the impulse response falls below half of its peak.
+    /// - 0 for a perfectly isotropic blur.
+    /// - 0.29 for a single pass box blur.
+    pub anisotropy: f32,
+}
+
+/// Impulse response of a blur setting, as computed by [`BlurAnalysis::impulse_response`].
+///
+/// The weights are normalized so that their sum is 1.
+#[derive(Clone, Debug)]
+pub struct ImpulseResponse {
+    texture: CpuTexture,
+}
+
+impl ImpulseResponse {
+    /// Size of the grid the impulse response has been computed on.
+    pub fn size(&self) -> UVec2 {
+        self.texture.size
+    }
+
+    /// Weight of the pixel at `position` in the grid.
+    pub fn weight(&self, position: UVec2) -> f32 {
+        self.texture.data[(position.y * self.texture.size.x + position.x) as usize]
+    }
+
+    /// Weighted mean position of the impulse response, in pixels.
+    pub fn centroid(&self) -> Vec2 {
+        self.positions()
+            .map(|(position, weight)| position * weight)
+            .sum()
+    }
+
+    /// Compares this impulse response with the kernel of a gaussian blur of the given `sigma`,
+    /// centered on the impulse response [centroid](Self::centroid).
+    pub fn compare_to_gaussian(&self, sigma: f32) -> BlurQualityMetrics {
+        let centroid = self.centroid();
+        let gaussian = |position: Vec2| {
+            if sigma > 0.0 {
+                (-(position - centroid).length_squared() / (2.0 * sigma * sigma)).exp()
+            } else if position == centroid {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        let gaussian_sum: f32 = self
+            .positions()
+            .map(|(position, _)| gaussian(position))
+            .sum();
+        let squared_error: f32 = self
+            .positions()
+            .map(|(position, weight)| (weight - gaussian(position) / gaussian_sum).powi(2))
+            .sum();
+        let rms_error = (squared_error / self.texture.data.len() as f32).sqrt();
+        let peak = gaussian(centroid) / gaussian_sum;
+        BlurQualityMetrics {
+            sigma,
+            rms_error,
+            psnr: 20.0 * (peak / rms_error).log10(),
+            anisotropy: self.anisotropy(),
+        }
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (Vec2, f32)> + '_ {
+        let width = self.texture.size.x;
+        self.texture
+            .data
+            .iter()
+            .enumerate()
+            .map(move |(i, &weight)| {
+                let position = UVec2::new(i as u32 % width, i as u32 / width);
+                (position.as_vec2(), weight)
+            })
+    }
+
+    fn interpolated_weight(&self, position: Vec2) -> f32 {
+        self.texture
+            .sample((position + 0.5) * self.texture.texel_size(), true)
+    }
+
+    fn anisotropy(&self) -> f32 {
+        let centroid = self.centroid();
+        let half_peak = self.interpolated_weight(centroid) / 2.0;
+        let max_distance = self.texture.size.max_element() as f32 / 2.0;
+        let half_peak_distance = |direction: Vec2| {
+            let mut distance = 0.0;
+            while distance < max_distance
+                && self.interpolated_weight(centroid + direction * distance) > half_peak
+            {
+                distance += 0.05;
+            }
+            distance
+        };
+        let mean_distance = |directions: [Vec2; 4]| {
+            directions.into_iter().map(half_peak_distance).sum::<f32>() / 4.0
+        };
+        let axes = mean_distance([Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y]);
+        let diagonals = mean_distance([
+            Vec2::ONE.normalize(),
+            Vec2::new(1.0, -1.0).normalize(),
+            Vec2::NEG_ONE.normalize(),
+            Vec2::new(-1.0, 1.0).normalize(),
+        ]);
+        if axes.max(diagonals) == 0.0 {
+            0.0
+        } else {
+            1.0 - axes.min(diagonals) / axes.max(diagonals)
+        }
+    }
+}
+
+impl BlurAnalysis for GaussianBlurSettings {
+    fn impulse_response(&self) -> ImpulseResponse {
+        let settings = self.create_concrete();
+        let taps = settings.taps();
+        let mut texture = CpuTexture::impulse(self.equivalent_sigma(), 1);
+        for direction in [Vec2::X, Vec2::Y] {
+            texture = texture.render(texture.size, |source, uv| {
+                let step = direction * settings.sampling_distance_factor * source.texel_size();
+                let center = taps[0].y * source.sample(uv, true);
+                center
+                    + taps[1..]
+                        .iter()
+                        .map(|tap| {
+                            tap.y
+                                * (source.sample(uv + tap.x * step, true)
+                                    + source.sample(uv - tap.x * step, true))
+                        })
+                        .sum::<f32>()
+            });
+        }
+        texture.into_impulse_response()
+    }
+}
+
+impl BlurAnalysis for BoxBlurSettings {
+    fn impulse_response(&self) -> ImpulseResponse {
+        let settings = self.create_concrete();
+        let mut texture = CpuTexture::impulse(self.equivalent_sigma(), 1);
+        for kernel_size in settings.pass_kernel_sizes() {
+            let upper = (kernel_size as i32 - 1) / 2;
+            for direction in [Vec2::X, Vec2::Y] {
+                texture = texture.render(texture.size, |source, uv| {
+                    (-upper..=upper)
+                        .map(|x| {
+                            let offset = direction * x as f32 * settings.sampling_distance_factor;
+                            source.sample(uv + offset * source.texel_size(), true)
+                        })
+                        .sum::<f32>()
+                        / kernel_size as f32
+                });
+            }
+        }
+        texture.into_impulse_response()
+    }
+}
+
+impl BlurAnalysis for KawaseBlurSettings {
+    fn impulse_response(&self) -> ImpulseResponse {
+        let settings = self.create_concrete();
+        let mut texture = CpuTexture::impulse(self.equivalent_sigma(), 1);
+        for &sampling_distance in settings.sampling_distances.iter() {
+            let d = sampling_distance + 0.5;
+            texture = texture.render(texture.size, |source, uv| {
+                [
+                    Vec2::new(d, d),
+                    Vec2::new(-d, d),
+                    Vec2::new(-d, -d),
+                    Vec2::new(d, -d),
+                ]
+                .into_iter()
+                .map(|offset| source.sample(uv + offset * source.texel_size(), true))
+                .sum::<f32>()
+                    / 4.0
+            });
+        }
+        texture.into_impulse_response()
+    }
+}
+
+impl BlurAnalysis for DualBlurSettings {
+    fn impulse_response(&self) -> ImpulseResponse {
+        let settings = self.create_concrete();
+        let source =
+            CpuTexture::impulse(self.equivalent_sigma(), 1 << settings.downsampling_passes());
+        let level_sizes = settings.level_sizes(source.size);
+        // Keeps the downsampled content of each level, the first one being the source.
+        let mut levels = vec![source];
+        for &size in level_sizes.iter() {
+            let texture = levels.last().unwrap().render(size, |source, uv| {
+                let halfpixel = source.texel_size() * 0.5 * settings.offset;
+                let sample = |offset: Vec2| source.sample(uv + offset * halfpixel, true);
+                (sample(Vec2::ZERO) * 4.0
+                    + sample(Vec2::new(1.0, 1.0))
+                    + sample(Vec2::new(1.0, -1.0))
+                    + sample(Vec2::new(-1.0, -1.0))
+                    + sample(Vec2::new(-1.0, 1.0)))
+                    / 8.0
+            });
+            levels.push(texture);
+        }
+        let mut texture = levels.pop().unwrap();
+        for i in (0..level_sizes.len()).rev() {
+            let destination = levels.pop().unwrap();
+            let upsampled = texture.render(destination.size, |source, uv| {
+                let halfpixel = source.texel_size() * 0.5 * settings.offset;
+                let sample = |offset: Vec2| source.sample(uv + offset * halfpixel, true);
+                (sample(Vec2::new(-2.0, 0.0))
+                    + sample(Vec2::new(2.0, 0.0))
+                    + sample(Vec2::new(0.0, 2.0))
+                    + sample(Vec2::new(0.0, -2.0))
+                    + sample(Vec2::new(-1.0, 1.0)) * 2.0
+                    + sample(Vec2::new(1.0, 1.0)) * 2.0
+                    + sample(Vec2::new(1.0, -1.0)) * 2.0
+                    + sample(Vec2::new(-1.0, -1.0)) * 2.0)
+                    / 12.0
+            });
+            // The last upsampling pass is blended with the downsampled content of its destination.
+            texture = if i + 1 == level_sizes.len() {
+                destination.mix(&upsampled, settings.last_pass_weight())
+            } else {
+                upsampled
+            };
+        }
+        texture.into_impulse_response()
+    }
+}
+
+impl BlurAnalysis for BlurPlan {
+    fn impulse_response(&self) -> ImpulseResponse {
+        match self {
+            BlurPlan::Gaussian(settings) => settings.impulse_response(),
+            BlurPlan::Box(settings) => settings.impulse_response(),
+            BlurPlan::Kawase(settings) => settings.impulse_response(),
+            BlurPlan::Dual(settings) => settings.impulse_response(),
+        }
+    }
+}
+
+impl KawaseBlurSettings {
+    /// Searches for the `sampling_distances` whose blur best approximates a gaussian blur of the given `sigma`,
+    /// using at most `max_passes` passes.
+    ///
+    /// The sampling distances are searched among multiples of 0.5 in `[0.0, 9.0]`, with a beam search
+    /// adding one pass at a time and keeping the most promising lists of sampling distances.
+    /// The selected one is the one whose impulse response has the lowest squared error against the gaussian kernel.
+    /// As the passes of the Kawase blur can be done in any order, the sampling distances are sorted.
+    ///
+    /// The work is bounded, at most 32 lists being kept after each pass: `max_passes` is clamped to 16,
+    /// and `sigma` to the highest equivalent sigma reachable with `max_passes` passes, about 38 for 16 passes.
+    ///
+    /// ```
+    ///# use bevy_camera_blur::*;
+    /// let settings = KawaseBlurSettings::fit_sigma(5.0, 4);
+    /// assert!(settings.sampling_distances.len() <= 4);
+    /// assert!((settings.equivalent_sigma() - 5.0).abs() < 0.5);
+    /// ```
+    pub fn fit_sigma(sigma: f32, max_passes: u32) -> KawaseBlurSettings {
+        let max_passes = max_passes.min(KawaseBlurSettings::MAX_PASSES);
+        let max_variance = max_passes as f32 * kawase_pass_variance(FIT_MAX_DISTANCE);
+        let variance = sigma.max(0.0).powi(2).min(max_variance);
+        // The Kawase blur being separable, with the same kernel on both axes,
+        // the search is done on one axis, with a kernel spanning distances in `-radius..=radius`.
+        let radius = (4.0 * variance.sqrt()).ceil() as usize + 2;
+        let gaussian = gaussian_kernel(variance, radius);
+        let max_candidate_variance = 1.5 * variance + 1.0;
+
+        let mut impulse = vec![0.0; 2 * radius + 1];
+        impulse[radius] = 1.0;
+        let mut best = (vec![], separable_error(&impulse, &gaussian));
+        // Each candidate keeps its sampling distances, its one axis impulse response and its variance.
+        let mut beam = vec![(vec![], impulse, 0.0)];
+        for pass in 0..max_passes {
+            // Variance the passes following a candidate can add at most.
+            let remaining_variance =
+                (max_passes - pass - 1) as f32 * kawase_pass_variance(FIT_MAX_DISTANCE);
+            let mut candidates = vec![];
+            for (distances, kernel, kernel_variance) in &beam {
+                let min_distance = distances.last().copied().unwrap_or(0.0);
+                for d in fit_distances().filter(|&d| d >= min_distance) {
+                    // Passes only add variance, candidates already too wide are dropped.
+                    let candidate_variance = kernel_variance + kawase_pass_variance(d);
+                    if candidate_variance > max_candidate_variance {
+                        break;
+                    }
+                    let candidate_kernel = kawase_pass(kernel, d);
+                    let error = separable_error(&candidate_kernel, &gaussian);
+                    let mut candidate_distances: Vec<f32> = distances.clone();
+                    candidate_distances.push(d);
+                    if error < best.1 {
+                        best = (candidate_distances.clone(), error);
+                    }
+                    // The candidates that can no longer reach the variance of the gaussian are not extended.
+                    if candidate_variance + remaining_variance < variance {
+                        continue;
+                    }
+                    // The candidates are ranked as if the missing variance was evenly split
+                    // between the next passes, as many as possible.
+                    let missing_variance = (variance - candidate_variance).max(0.0);
+                    let next_passes = ((missing_variance / kawase_pass_variance(0.0)) as u32)
+                        .min(max_passes - pass - 1);
+                    let mut completed = candidate_kernel.clone();
+                    if next_passes > 0 {
+                        let d = kawase_pass_distance(missing_variance / next_passes as f32);
+                        for _ in 0..next_passes {
+                            completed = kawase_pass(&completed, d);
+                        }
+                    }
+                    let score = separable_error(&completed, &gaussian);
+                    candidates.push((
+                        score,
+                        (candidate_distances, candidate_kernel, candidate_variance),
+                    ));
+                }
+            }
+            if candidates.is_empty() {
+                break;
+            }
+            candidates.sort_by(|(score1, _), (score2, _)| score1.total_cmp(score2));
+            beam = candidates
+                .into_iter()
+                .take(FIT_BEAM_WIDTH)
+                .map(|(_, candidate)| candidate)
+                .collect();
+        }
+        KawaseBlurSettings {
+            sampling_distances: best.0,
+            ..KawaseBlurSettings::default()
+        }
+    }
+}
+
+// Number of candidates kept after each pass by `KawaseBlurSettings::fit_sigma`,
+// at most `KawaseBlurSettings::MAX_PASSES * FIT_BEAM_WIDTH * 19` candidates being evaluated.
+const FIT_BEAM_WIDTH: usize = 32;
+// Highest sampling distance searched by `KawaseBlurSettings::fit_sigma`, as clamped by the shader.
+const FIT_MAX_DISTANCE: f32 = 9.0;
+
+// Sampling distances searched by `KawaseBlurSettings::fit_sigma`, by increasing order.
+fn fit_distances() -> impl Iterator<Item = f32> {
+    (0..=(FIT_MAX_DISTANCE * 2.0) as u32).map(|i| i as f32 * 0.5)
+}
+
+// One axis gaussian kernel of the given `variance`, normalized, spanning distances in `-radius..=radius`.
+fn gaussian_kernel(variance: f32, radius: usize) -> Vec<f32> {
+    let radius = radius as i32;
+    let weights = (-radius..=radius)
+        .map(|x| (-(x * x) as f32 / (2.0 * variance.max(0.0001))).exp())
+        .collect::<Vec<_>>();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+// Squared error between the 2D kernels being the outer products of the one axis kernels `kernel` and `gaussian`.
+// The sum over the 2D kernels `(k1*k2 - g1*g2)^2` factorizes into sums over one axis.
+fn separable_error(kernel: &[f32], gaussian: &[f32]) -> f32 {
+    let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(&a, &b)| a * b).sum::<f32>();
+    let (kk, kg, gg) = (
+        dot(kernel, kernel),
+        dot(kernel, gaussian),
+        dot(gaussian, gaussian),
+    );
+    (kk * kk - 2.0 * kg * kg + gg * gg).max(0.0)
+}
+
+// Sampling distance of the Kawase pass of the given `variance`, the inverse of `kawase_pass_variance`.
+fn kawase_pass_distance(variance: f32) -> f32 {
+    let variance = variance.clamp(
+        kawase_pass_variance(0.0),
+        kawase_pass_variance(FIT_MAX_DISTANCE),
+    );
+    let base = variance.sqrt().floor();
+    let t = (variance - base * base) / (2.0 * base + 1.0);
+    base + t - 0.5
+}
+
+// Applies on a one axis kernel a Kawase pass of sampling distance `d`, averaging the bilinear samples
+// done at `d+0.5` and `-(d+0.5)`. The weights beyond the kernel are zero.
+fn kawase_pass(kernel: &[f32], d: f32) -> Vec<f32> {
+    let offset = d + 0.5;
+    let (base, t) = (offset.floor() as i32, offset.fract());
+    let len = kernel.len() as i32;
+    let weight = |i: i32| {
+        if (0..len).contains(&i) {
+            kernel[i as usize]
+        } else {
+            0.0
+        }
+    };
+    (0..len)
+        .map(|i| {
+            let sample = |direction: i32| {
+                weight(i + direction * base) * (1.0 - t) + weight(i + direction * (base + 1)) * t
+            };
+            (sample(1) + sample(-1)) / 2.0
+        })
+        .collect()
+}
+
+// Single channel image emulating a texture sampled by the post-processing shaders.
+#[derive(Clone, Debug)]
+struct CpuTexture {
+    size: UVec2,
+    data: Vec<f32>,
+}
+
+impl CpuTexture {
+    // Creates a texture with a single lit pixel in its middle, large enough for a blur of `sigma`.
+    // Its size is a multiple of `alignment`.
+    fn impulse(sigma: f32, alignment: u32) -> CpuTexture {
+        let side = 2 * ((4.0 * sigma).ceil() as u32 + 2) + 1;
+        let side = side.div_ceil(alignment) * alignment;
+        let size = UVec2::splat(side);
+        let mut data = vec![0.0; (side * side) as usize];
+        data[((side / 2) * side + side / 2) as usize] = 1.0;
+        CpuTexture { size, data }
+    }
+
+    fn texel_size(&self) -> Vec2 {
+        1.0 / self.size.as_vec2()
+    }
+
+    // Samples the texture at `uv` with a clamp to edge addressing,
+    // and a linear or nearest filtering.
+    fn sample(&self, uv: Vec2, linear: bool) -> f32 {
+        let texel = |position: IVec2| {
+            let position = position.clamp(IVec2::ZERO, self.size.as_ivec2() - 1);
+            self.data[(position.y as u32 * self.size.x + position.x as u32) as usize]
+        };
+        let position = uv * self.size.as_vec2() - 0.5;
+        if linear {
+            let base = position.floor();
+            let t = position - base;
+            let base = base.as_ivec2();
+            let top = texel(base) * (1.0 - t.x) + texel(base + IVec2::X) * t.x;
+            let bottom = texel(base + IVec2::Y) * (1.0 - t.x) + texel(base + IVec2::ONE) * t.x;
+            top * (1.0 - t.y) + bottom * t.y
+        } else {
+            texel((uv * self.size.as_vec2()).floor().as_ivec2())
+        }
+    }
+
+    // Emulates a fullscreen pass rendering to a texture of `size`,
+    // `fragment` computing the value of each pixel from its `uv`.
+    fn render(&self, size: UVec2, fragment: impl Fn(&CpuTexture, Vec2) -> f32) -> CpuTexture {
+        let data = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| UVec2::new(x, y)))
+            .map(|position| fragment(self, (position.as_vec2() + 0.5) / size.as_vec2()))
+            .collect();
+        CpuTexture { size, data }
+    }
+
+    // Emulates the blending of `other` over this texture of the same size, with a `weight`.
+    fn mix(&self, other: &CpuTexture, weight: f32) -> CpuTexture {
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(&v1, &v2)| v1 * (1.0 - weight) + v2 * weight)
+            .collect();
+        CpuTexture {
+            size: self.size,
+            data,
+        }
+    }
+
+    fn into_impulse_response(self) -> ImpulseResponse {
+        let sum: f32 = self.data.iter().sum();
+        ImpulseResponse {
+            texture: CpuTexture {
+                size: self.size,
+                data: self.data.into_iter().map(|w| w / sum).collect(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fitted_kawase_blurs_are_closer_to_a_gaussian() {
+        for sigma in [1.2, 2.0, 5.0, 10.0] {
+            let fitted = KawaseBlurSettings::fit_sigma(sigma, 8);
+            assert!(fitted.passes() <= 8);
+            let equivalent_sigma = fitted.equivalent_sigma();
+            // The lowest error can be reached for a slightly different equivalent sigma.
+            assert!((equivalent_sigma / sigma - 1.0).abs() < 0.2, "{fitted:?}");
+            let error = fitted
+                .impulse_response()
+                .compare_to_gaussian(sigma)
+                .rms_error;
+            let from_sigma = KawaseBlurSettings::from_sigma(sigma);
+            let from_sigma_error = from_sigma
+                .impulse_response()
+                .compare_to_gaussian(sigma)
+                .rms_error;
+            assert!(error <= from_sigma_error, "{error} > {from_sigma_error}");
+        }
+    }
+
+    #[test]
+    fn fitted_kawase_blurs_use_half_pixel_distances() {
+        let fitted = KawaseBlurSettings::fit_sigma(3.3, 16);
+        assert!(fitted.sampling_distances.iter().any(|d| d.fract() == 0.5));
+    }
+
+    #[test]
+    fn kawase_fit_is_bounded() {
+        assert!(KawaseBlurSettings::fit_sigma(0.0, 4)
+            .sampling_distances
+            .is_empty());
+        assert!(KawaseBlurSettings::fit_sigma(f32::NAN, 4)
+            .sampling_distances
+            .is_empty());
+        for sigma in [1000.0, f32::INFINITY] {
+            let fitted = KawaseBlurSettings::fit_sigma(sigma, u32::MAX);
+            assert_eq!(fitted.passes(), KawaseBlurSettings::MAX_PASSES);
+            assert!(fitted.sampling_distances.iter().all(|&d| d <= 9.0));
+        }
+    }
+
+    #[test]
+    fn kawase_pass_distance_is_the_inverse_of_the_variance() {
+        for d in [0.0, 0.3, 1.0, 2.5, 9.0] {
+            assert!((kawase_pass_distance(kawase_pass_variance(d)) - d).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn quality_metrics_compare_to_a_gaussian() {
+        let sharp = KawaseBlurSettings::NO_BLUR.impulse_response();
+        let metrics = sharp.compare_to_gaussian(0.0);
+        assert_eq!(metrics.rms_error, 0.0);
+
+        let gaussian = GaussianBlurSettings::from_sigma(4.0).quality();
+        assert!(gaussian.psnr > 40.0, "{gaussian:?}");
+        assert!(gaussian.anisotropy < 0.05, "{gaussian:?}");
+        // The error grows with a wrong reference sigma.
+        let response = GaussianBlurSettings::from_sigma(4.0).impulse_response();
+        assert!(response.compare_to_gaussian(6.0).rms_error > gaussian.rms_error);
+
+        let box_blur = BoxBlurSettings {
+            kernel_size: 9,
+            passes: 1,
+            ..BoxBlurSettings::default()
+        }
+        .quality();
+        assert!((box_blur.anisotropy - 0.29).abs() < 0.05, "{box_blur:?}");
+        assert!(box_blur.psnr < gaussian.psnr);
+    }
+}
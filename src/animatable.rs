@@ -0,0 +1,108 @@
+#![cfg(feature = "bevy_animation")]
+use bevy::animation::prelude::{Animatable, BlendInput};
+
+use crate::{
+    BlurSetting, BoxBlurSettings, DualBlurSettings, GaussianBlurSettings, KawaseBlurSettings,
+};
+
+// Blends the inputs as a running weighted average, each input being interpolated with the previous result
+// by its share of the weights so far. When the weights sum to less than 1, `NO_BLUR` gets the remaining weight.
+// Blur settings can't be summed, so additive inputs are blended the same way.
+fn blend_settings<S: BlurSetting + Clone>(inputs: impl Iterator<Item = BlendInput<S>>) -> S {
+    let inputs: Vec<_> = inputs.collect();
+    let total_weight: f32 = inputs.iter().map(|input| input.weight.max(0.0)).sum();
+    let mut weight = (1.0 - total_weight).max(0.0);
+    inputs.into_iter().fold(S::NO_BLUR, |value, input| {
+        weight += input.weight.max(0.0);
+        match input.weight.max(0.0) / weight {
+            share if share >= 1.0 => input.value,
+            share if share > 0.0 => value.interpolate(&input.value, share),
+            _ => value,
+        }
+    })
+}
+
+/// Allows the gaussian blur to be driven by Bevy's animation system.
+///
+/// See [`BlurSetting::interpolate`] for the interpolation details.
+impl Animatable for GaussianBlurSettings {
+    fn interpolate(a: &Self, b: &Self, time: f32) -> Self {
+        BlurSetting::interpolate(a, b, time)
+    }
+
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        blend_settings(inputs)
+    }
+}
+
+/// Allows the box blur to be driven by Bevy's animation system.
+///
+/// See [`BlurSetting::interpolate`] for the interpolation details.
+impl Animatable for BoxBlurSettings {
+    fn interpolate(a: &Self, b: &Self, time: f32) -> Self {
+        BlurSetting::interpolate(a, b, time)
+    }
+
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        blend_settings(inputs)
+    }
+}
+
+/// Allows the Kawase blur to be driven by Bevy's animation system.
+///
+/// See [`BlurSetting::interpolate`] for the interpolation details,
+/// and how sampling distances lists of different lengths are handled.
+impl Animatable for KawaseBlurSettings {
+    fn interpolate(a: &Self, b: &Self, time: f32) -> Self {
+        BlurSetting::interpolate(a, b, time)
+    }
+
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        blend_settings(inputs)
+    }
+}
+
+/// Allows the dual blur to be driven by Bevy's animation system.
+///
+/// See [`BlurSetting::interpolate`] for the interpolation details.
+impl Animatable for DualBlurSettings {
+    fn interpolate(a: &Self, b: &Self, time: f32) -> Self {
+        BlurSetting::interpolate(a, b, time)
+    }
+
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        blend_settings(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: GaussianBlurSettings, weight: f32) -> BlendInput<GaussianBlurSettings> {
+        BlendInput {
+            weight,
+            value,
+            additive: false,
+        }
+    }
+
+    #[test]
+    fn blend_averages_the_inputs() {
+        let a = GaussianBlurSettings::from_sigma(2.0);
+        let b = GaussianBlurSettings::from_sigma(12.0);
+        assert_eq!(
+            <GaussianBlurSettings as Animatable>::blend([input(a, 1.0)].into_iter()),
+            a
+        );
+        assert_eq!(
+            <GaussianBlurSettings as Animatable>::blend([input(a, 0.5), input(b, 0.5)].into_iter()),
+            BlurSetting::interpolate(&a, &b, 0.5)
+        );
+        // The missing weight goes to `NO_BLUR`.
+        assert_eq!(
+            <GaussianBlurSettings as Animatable>::blend([input(b, 0.5)].into_iter()),
+            BlurSetting::interpolate(&GaussianBlurSettings::NO_BLUR, &b, 0.5)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    BlurPlan, BlurPlanner, BlurSetting, BlurTransition, BlurUnits, BoxBlurPlugin, BoxBlurSettings,
+    DualBlurPlugin, DualBlurSettings, GaussianBlurPlugin, GaussianBlurSettings, KawaseBlurPlugin,
+    KawaseBlurSettings,
+};
+
+/// This plugin adapts the blur of the cameras with a [`BlurAutoQuality`] component to a frame time budget.
+///
+/// The blur plugins of the algorithms that can be selected must be added to the app,
+/// the other algorithms being never selected.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///pub fn setup(mut commands: Commands) {
+///    commands.spawn((
+///        Camera2dBundle::default(),
+///        GaussianBlurSettings::default(),
+///        // Targets 60 frames per second.
+///        BlurAutoQuality::new(16.6),
+///    ));
+///}
+///```
+pub struct BlurAutoQualityPlugin;
+
+impl Plugin for BlurAutoQualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, adapt_blur_quality);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let algorithms = AvailableBlurAlgorithms {
+            gaussian: app.is_plugin_added::<GaussianBlurPlugin>(),
+            box_blur: app.is_plugin_added::<BoxBlurPlugin>(),
+            kawase: app.is_plugin_added::<KawaseBlurPlugin>(),
+            dual: app.is_plugin_added::<DualBlurPlugin>(),
+        };
+        app.insert_resource(algorithms);
+    }
+}
+
+/// Adapts the blur of a camera to a frame time budget, in conjonction with the [`BlurAutoQualityPlugin`].
+///
+/// The blur settings component of the camera, as set by the game, is the highest quality used.
+/// While the frame time is over `target_ms`, it is replaced by cheaper settings, of any algorithm,
+/// planned by the [`BlurPlanner`] for the same equivalent sigma, so that the scene looks as blurry.
+/// The quality is raised back once the frame time is well under `target_ms`.
+///
+/// The settings component of the camera is overwritten, and can even be replaced by the settings component
+/// of another algorithm: the settings set by the game are kept by this component, see [`BlurAutoQuality::authored`].
+/// The settings changed by the game become the new highest quality.
+/// Settings that do not blur are left untouched.
+/// The camera is left untouched while one of its settings is animated by a [`BlurTransition`].
+#[derive(Component, Clone, Debug)]
+pub struct BlurAutoQuality {
+    /// Targeted frame time, in milliseconds.
+    pub target_ms: f32,
+    // Smoothed frame time, in milliseconds.
+    frame_ms: Option<f32>,
+    // Settings set by the game, in the units of the camera.
+    authored: Option<BlurPlan>,
+    // Settings currently used by the camera, in the units of the camera.
+    planned: Option<BlurPlan>,
+    // Number of texture sampling allowed for each frame.
+    texture_sampling_budget: u64,
+    // Time left before the next change of quality, for the frame time to follow it.
+    cooldown: Duration,
+}
+
+impl BlurAutoQuality {
+    /// Creates the component for a targeted frame time of `target_ms` milliseconds.
+    pub fn new(target_ms: f32) -> Self {
+        Self {
+            target_ms,
+            frame_ms: None,
+            authored: None,
+            planned: None,
+            texture_sampling_budget: u64::MAX,
+            cooldown: Duration::ZERO,
+        }
+    }
+
+    /// Settings of the camera as set by the game, in the units of the camera,
+    /// used by the highest quality.
+    ///
+    /// `None` until the component has been updated once.
+    pub fn authored(&self) -> Option<&BlurPlan> {
+        self.authored.as_ref()
+    }
+
+    // Updates the quality after a frame of `frame_time`, the camera using the `current` settings,
+    // with `factor` physical pixels per unit of the settings.
+    // Returns the settings replacing the current ones, if any.
+    fn update(
+        &mut self,
+        frame_time: Duration,
+        current: BlurPlan,
+        factor: f32,
+        viewport_size: UVec2,
+        algorithms: &AvailableBlurAlgorithms,
+    ) -> Option<BlurPlan> {
+        let frame_ms = frame_time.as_secs_f32() * 1000.0;
+        self.frame_ms = Some(match self.frame_ms {
+            Some(smoothed) => smoothed + (frame_ms - smoothed) * FRAME_TIME_SMOOTHING,
+            None => frame_ms,
+        });
+        self.cooldown = self.cooldown.saturating_sub(frame_time);
+
+        if self.planned.as_ref() != Some(&current) {
+            self.texture_sampling_budget = current.scaled(factor).texture_samplings(viewport_size);
+            self.authored = Some(current.clone());
+            self.planned = Some(current);
+            return None;
+        }
+        let authored = self.authored.clone()?;
+        if !self.cooldown.is_zero() {
+            return None;
+        }
+
+        let authored_pixels = authored.scaled(factor);
+        // Settings that do not blur have no cheaper alternative.
+        if authored_pixels.equivalent_sigma() <= 0.0 {
+            return None;
+        }
+        let authored_budget = authored_pixels.texture_samplings(viewport_size);
+        let current_cost = current.scaled(factor).texture_samplings(viewport_size);
+        let budget = self.texture_sampling_budget;
+        let frame_ms = self.frame_ms.unwrap_or(frame_ms);
+        let lowering = frame_ms > self.target_ms;
+        let budget = if lowering {
+            (budget.min(current_cost) as f64 * BUDGET_STEP) as u64
+        } else if frame_ms < self.target_ms * RAISE_QUALITY_THRESHOLD && budget < authored_budget {
+            ((budget as f64 / BUDGET_STEP) as u64).min(authored_budget)
+        } else {
+            return None;
+        };
+        self.texture_sampling_budget = budget;
+        self.cooldown = QUALITY_COOLDOWN;
+
+        let (plan, cost) = if budget >= authored_budget {
+            (authored, authored_budget)
+        } else {
+            let planner =
+                BlurPlanner::new(authored_pixels.equivalent_sigma(), viewport_size, budget);
+            let is_available = |plan: &BlurPlan| algorithms.contains(plan);
+            // Without any setting within the budget, the cheapest one is used.
+            let plan = planner.plan_among(is_available).or_else(|| {
+                BlurPlanner {
+                    texture_sampling_budget: u64::MAX,
+                    ..planner.clone()
+                }
+                .plan_among(is_available)
+            })?;
+            let cost = plan.texture_samplings(viewport_size);
+            (plan.scaled(1.0 / factor).with_modes(authored.modes()), cost)
+        };
+        // The quality is never lowered to more expensive settings.
+        if lowering && cost >= current_cost {
+            self.texture_sampling_budget = current_cost;
+            return None;
+        }
+        if plan == current {
+            return None;
+        }
+        self.planned = Some(plan.clone());
+        Some(plan)
+    }
+}
+
+// Algorithms whose plugin has been added to the app.
+#[derive(Resource)]
+struct AvailableBlurAlgorithms {
+    gaussian: bool,
+    box_blur: bool,
+    kawase: bool,
+    dual: bool,
+}
+
+impl AvailableBlurAlgorithms {
+    fn contains(&self, plan: &BlurPlan) -> bool {
+        match plan {
+            BlurPlan::Gaussian(_) => self.gaussian,
+            BlurPlan::Box(_) => self.box_blur,
+            BlurPlan::Kawase(_) => self.kawase,
+            BlurPlan::Dual(_) => self.dual,
+        }
+    }
+}
+
+// Weight of the last frame in the smoothed frame time.
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+// The quality is only raised when the frame time is under this fraction of the targeted one.
+const RAISE_QUALITY_THRESHOLD: f32 = 0.8;
+// Factor applied to the texture sampling budget when lowering the quality, its inverse when raising it.
+const BUDGET_STEP: f64 = 0.75;
+// Time between two changes of quality.
+const QUALITY_COOLDOWN: Duration = Duration::from_millis(500);
+
+type AutoQualityCameraData = (
+    Entity,
+    &'static mut BlurAutoQuality,
+    &'static Camera,
+    Option<&'static BlurUnits>,
+    Option<&'static OrthographicProjection>,
+    Option<&'static Projection>,
+    AnyOf<(
+        &'static GaussianBlurSettings,
+        &'static BoxBlurSettings,
+        &'static KawaseBlurSettings,
+        &'static DualBlurSettings,
+    )>,
+);
+
+type WithoutTransitions = (
+    Without<BlurTransition<GaussianBlurSettings>>,
+    Without<BlurTransition<BoxBlurSettings>>,
+    Without<BlurTransition<KawaseBlurSettings>>,
+    Without<BlurTransition<DualBlurSettings>>,
+);
+
+fn adapt_blur_quality(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    algorithms: Res<AvailableBlurAlgorithms>,
+    mut cameras: Query<AutoQualityCameraData, WithoutTransitions>,
+) {
+    for (entity, mut auto_quality, camera, units, orthographic_projection, projection, settings) in
+        &mut cameras
+    {
+        let Some(viewport_size) = camera.physical_viewport_size() else {
+            continue;
+        };
+        let current = match settings {
+            (Some(settings), ..) => BlurPlan::Gaussian(*settings),
+            (_, Some(settings), ..) => BlurPlan::Box(settings.clone()),
+            (_, _, Some(settings), _) => BlurPlan::Kawase(settings.clone()),
+            (.., Some(settings)) => BlurPlan::Dual(settings.clone()),
+            _ => continue,
+        };
+        // The planner works in physical pixels.
+        let factor = units.copied().unwrap_or_default().physical_pixels_per_unit(
+            camera,
+            orthographic_projection,
+            projection,
+        );
+        if let Some(plan) =
+            auto_quality.update(time.delta(), current, factor, viewport_size, &algorithms)
+        {
+            plan.insert(&mut commands.entity(entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT_SIZE: UVec2 = UVec2::new(1920, 1080);
+
+    // Runs frames of `frame_ms` milliseconds for `seconds`, applying the settings changes to `current`.
+    // Returns the settings changes.
+    fn run_frames(
+        auto_quality: &mut BlurAutoQuality,
+        current: &mut BlurPlan,
+        frame_ms: u64,
+        seconds: u64,
+        algorithms: &AvailableBlurAlgorithms,
+    ) -> Vec<BlurPlan> {
+        let mut changes = vec![];
+        for _ in 0..seconds * 1000 / frame_ms {
+            let frame_time = Duration::from_millis(frame_ms);
+            if let Some(plan) =
+                auto_quality.update(frame_time, current.clone(), 1.0, VIEWPORT_SIZE, algorithms)
+            {
+                *current = plan.clone();
+                changes.push(plan);
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn quality_follows_the_frame_time() {
+        let algorithms = AvailableBlurAlgorithms {
+            gaussian: true,
+            box_blur: false,
+            kawase: false,
+            dual: true,
+        };
+        let authored = BlurPlan::Gaussian(GaussianBlurSettings::from_sigma(12.0));
+        let mut auto_quality = BlurAutoQuality::new(16.0);
+        let mut current = authored.clone();
+        let cost = |plan: &BlurPlan| plan.texture_samplings(VIEWPORT_SIZE);
+
+        // Slow frames lower the cost, keeping about the same blur with the available algorithms.
+        let lowered = run_frames(&mut auto_quality, &mut current, 30, 10, &algorithms);
+        assert!(!lowered.is_empty());
+        for plan in &lowered {
+            assert!(algorithms.contains(plan), "{plan:?}");
+            assert!(
+                (plan.equivalent_sigma() / 12.0 - 1.0).abs() <= 0.1,
+                "{plan:?}"
+            );
+        }
+        assert!(cost(&current) < cost(&authored));
+        assert_eq!(auto_quality.authored(), Some(&authored));
+
+        // Fast frames raise the quality back to the authored settings.
+        let raised = run_frames(&mut auto_quality, &mut current, 5, 30, &algorithms);
+        assert!(!raised.is_empty());
+        assert_eq!(current, authored);
+    }
+
+    #[test]
+    fn settings_without_blur_are_untouched() {
+        let algorithms = AvailableBlurAlgorithms {
+            gaussian: false,
+            box_blur: false,
+            kawase: false,
+            dual: true,
+        };
+        let mut auto_quality = BlurAutoQuality::new(16.0);
+        let mut current = BlurPlan::NO_BLUR;
+        let changes = run_frames(&mut auto_quality, &mut current, 30, 10, &algorithms);
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+}
@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    render::render_resource::{BindGroup, BufferId, TextureView, TextureViewId},
+};
+
+/// Bind groups of each view, kept across frames, for the blur whose pipeline is `P`.
+///
+/// The bind groups of a view are only created again when the sampled textures or the uniform buffer
+/// have changed, for example on a resize or on a settings change.
+#[derive(Resource)]
+pub(crate) struct ViewBindGroups<P> {
+    views: EntityHashMap<CachedBindGroups>,
+    marker: PhantomData<P>,
+}
+
+impl<P> Default for ViewBindGroups<P> {
+    fn default() -> Self {
+        Self {
+            views: EntityHashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Bind groups of a view, one for each texture that can be sampled by its passes.
+pub(crate) struct CachedBindGroups {
+    // Uniform buffer bound by all the bind groups.
+    buffer: Option<BufferId>,
+    // Other texture views bound by all the bind groups.
+    bound: Vec<TextureViewId>,
+    bind_groups: Vec<(TextureViewId, BindGroup)>,
+}
+
+impl CachedBindGroups {
+    /// Provides the bind group sampling `source`.
+    ///
+    /// Panics if `source` was not one of the sources of the view when its bind groups were prepared.
+    pub(crate) fn get(&self, source: &TextureView) -> &BindGroup {
+        self.bind_groups
+            .iter()
+            .find(|(id, _)| *id == source.id())
+            .map(|(_, bind_group)| bind_group)
+            .expect("bind groups are prepared for every source of the view")
+    }
+
+    fn is_valid(
+        &self,
+        sources: &[&TextureView],
+        bound: &[&TextureView],
+        buffer: Option<BufferId>,
+    ) -> bool {
+        self.buffer == buffer
+            && self.bound.len() == bound.len()
+            && self
+                .bound
+                .iter()
+                .zip(bound)
+                .all(|(id, view)| *id == view.id())
+            && self.bind_groups.len() == sources.len()
+            && self
+                .bind_groups
+                .iter()
+                .zip(sources)
+                .all(|((id, _), source)| *id == source.id())
+    }
+}
+
+impl<P> ViewBindGroups<P> {
+    /// Drops the bind groups of the views for which `keep` returns `false`.
+    pub(crate) fn retain(&mut self, keep: impl Fn(Entity) -> bool) {
+        self.views.retain(|&view, _| keep(view));
+    }
+
+    /// Updates the bind groups of `view`, one for each of the `sources`, all of them binding `buffer`.
+    ///
+    /// `create` is only called when the cached bind groups of `view` are no longer valid.
+    pub(crate) fn update(
+        &mut self,
+        view: Entity,
+        sources: &[&TextureView],
+        buffer: Option<BufferId>,
+        create: impl Fn(&TextureView) -> BindGroup,
+    ) {
+        self.update_binding(view, sources, &[], buffer, create);
+    }
+
+    /// Same as [`ViewBindGroups::update`], for bind groups also binding each of the `bound` texture views.
+    pub(crate) fn update_binding(
+        &mut self,
+        view: Entity,
+        sources: &[&TextureView],
+        bound: &[&TextureView],
+        buffer: Option<BufferId>,
+        create: impl Fn(&TextureView) -> BindGroup,
+    ) {
+        if self
+            .views
+            .get(&view)
+            .is_some_and(|cached| cached.is_valid(sources, bound, buffer))
+        {
+            return;
+        }
+        let bind_groups = sources
+            .iter()
+            .map(|source| (source.id(), create(source)))
+            .collect();
+        self.views.insert(
+            view,
+            CachedBindGroups {
+                buffer,
+                bound: bound.iter().map(|view| view.id()).collect(),
+                bind_groups,
+            },
+        );
+    }
+
+    /// Provides the bind groups of `view`, if they have been prepared.
+    pub(crate) fn get(&self, view: Entity) -> Option<&CachedBindGroups> {
+        self.views.get(&view)
+    }
+}
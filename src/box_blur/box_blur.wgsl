@@ -3,41 +3,72 @@
 // The effect uses two passes, an horizontal pass and a vertical pass
 
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_camera_blur::edge_mode::{edge_coverage, edge_normalize, edge_sample, viewport_texel_size, viewport_uv}
+#import bevy_camera_blur::alpha_mode::{alpha_finish, alpha_premultiply}
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
 struct BoxBlurUniforms {
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    // The UV coordinates of the passes are relative to it, the render passes being restricted to the viewport.
+    viewport: vec4<f32>,
     kernel_size: i32,
-    _passes: i32,
-    // WebGL2 structs must be 16 byte aligned.
-    _webgl2_padding: vec2<f32>,
+    sampling_distance_factor: f32,
+    // Value of the `BlurEdgeMode`
+    edge_mode: u32,
+    // Value of the `BlurAlphaMode`
+    alpha_mode: u32,
 }
 @group(0) @binding(2) var<uniform> settings: BoxBlurUniforms;
 
+const KERNEL_RADIUS: i32 = #{KERNEL_RADIUS};
+
+// Samples the pixel at 'uv', beyond the edges and premultiplied according to the settings.
+fn blur_sample(uv: vec2<f32>) -> vec4<f32> {
+    let sample = edge_sample(screen_texture, texture_sampler, uv, settings.viewport, settings.edge_mode);
+    return alpha_premultiply(sample, settings.alpha_mode);
+}
+
 @fragment
 fn fragment_horizontal(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let upper = (settings.kernel_size - 1) / 2;
-    let lower = -upper;
     var color = vec4(0.0);
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    for (var x = lower; x <= upper ; x ++) {
-        let uv = in.uv + vec2<f32>(f32(x) * texel_size.x, 0.);
-        color += textureSample(screen_texture, texture_sampler, uv);
+    var coverage = 0.0;
+    let texel_size = viewport_texel_size(screen_texture, settings.viewport);
+    // The pipeline is specialized on the kernel radius, so that the loop can be unrolled.
+    // It can be higher than the radius of the settings, the additional pixels having a null weight.
+    for (var x = -KERNEL_RADIUS; x <= KERNEL_RADIUS ; x ++) {
+        let uv = in.uv + vec2<f32>(f32(x) * settings.sampling_distance_factor * texel_size.x, 0.);
+        let weight = select(0.0, 1.0, abs(x) <= upper) / f32(settings.kernel_size);
+        color += weight * blur_sample(uv);
+        coverage += weight * edge_coverage(uv, settings.edge_mode);
     }
-    return color / f32(settings.kernel_size);
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    let texture_uv = viewport_uv(screen_texture, in.uv, settings.viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
 }
 @fragment
 fn fragment_vertical(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let upper = (settings.kernel_size - 1) / 2;
-    let lower = -upper;
     var color = vec4(0.0);
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    for (var y = lower; y <= upper ; y ++) {
-        let uv = in.uv + vec2<f32>(0., f32(y) * texel_size.y);
-        color += textureSample(screen_texture, texture_sampler, uv);
+    var coverage = 0.0;
+    let texel_size = viewport_texel_size(screen_texture, settings.viewport);
+    // The pipeline is specialized on the kernel radius, so that the loop can be unrolled.
+    // It can be higher than the radius of the settings, the additional pixels having a null weight.
+    for (var y = -KERNEL_RADIUS; y <= KERNEL_RADIUS ; y ++) {
+        let uv = in.uv + vec2<f32>(0., f32(y) * settings.sampling_distance_factor * texel_size.y);
+        let weight = select(0.0, 1.0, abs(y) <= upper) / f32(settings.kernel_size);
+        color += weight * blur_sample(uv);
+        coverage += weight * edge_coverage(uv, settings.edge_mode);
     }
-    return color / f32(settings.kernel_size);
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    let texture_uv = viewport_uv(screen_texture, in.uv, settings.viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
 }
 
+
+// Copies the result of the compute shaders, written in the viewport of a texture of the size of the view target.
+@fragment
+fn fragment_copy(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(screen_texture, texture_sampler, viewport_uv(screen_texture, in.uv, settings.viewport));
+}
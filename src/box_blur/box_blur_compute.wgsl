@@ -0,0 +1,65 @@
+// This shader computes the box blur effect with compute shaders, in constant time of the kernel size
+
+// Each pass is done in two dispatches, an horizontal one and a vertical one.
+// Each line (or column) of the texture is split in segments of SEGMENT_LENGTH pixels, each invocation scanning
+// one segment while keeping the running sum of the kernel:
+// moving the kernel by one pixel adds the pixel entering the kernel and removes the pixel leaving it.
+// The sum is computed again from all the pixels of the kernel at the start of each segment,
+// so that the rounding errors of the running sum do not accumulate along the line,
+// and the segments of a line are blurred in parallel.
+// Only the viewport of the camera is blurred, the textures having the size of the view target.
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba16float, write>;
+struct BoxBlurUniforms {
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    viewport: vec4<f32>,
+    kernel_size: i32,
+    // Always 1, the compute shaders being only used without sampling distance factor.
+    _sampling_distance_factor: f32,
+    // Always clamping, the compute shaders being only used with `BlurEdgeMode::Clamp`.
+    _edge_mode: u32,
+    // Always straight, the compute shaders being only used with `BlurAlphaMode::Straight`.
+    _alpha_mode: u32,
+}
+@group(0) @binding(2) var<uniform> settings: BoxBlurUniforms;
+
+// Edge pixels of the viewport at 'origin' are repeated, as with the clamped sampling of the fragment shaders.
+fn load(origin: vec2<i32>, axis: vec2<i32>, line: i32, position: i32, length: i32) -> vec4<f32> {
+    let other_axis = vec2(1) - axis;
+    return textureLoad(source_texture, origin + axis * clamp(position, 0, length - 1) + other_axis * line, 0);
+}
+
+// Blurs the segment `segment` of the line `line` of the viewport along `axis`.
+fn blur(line: i32, segment: i32, axis: vec2<i32>) {
+    let texture_size = vec2<f32>(textureDimensions(source_texture));
+    let origin = vec2<i32>(round(settings.viewport.xy * texture_size));
+    let size = vec2<i32>(round(settings.viewport.zw * texture_size)) - origin;
+    let other_axis = vec2(1) - axis;
+    let length = dot(size, axis);
+    let start = segment * #{SEGMENT_LENGTH};
+    if line >= dot(size, other_axis) || start >= length {
+        return;
+    }
+    let end = min(start + #{SEGMENT_LENGTH}, length);
+    let radius = (settings.kernel_size - 1) / 2;
+
+    var sum = vec4(0.0);
+    for (var i = start - radius; i <= start + radius; i++) {
+        sum += load(origin, axis, line, i, length);
+    }
+    for (var position = start; position < end; position++) {
+        textureStore(output_texture, origin + axis * position + other_axis * line, sum / f32(settings.kernel_size));
+        sum += load(origin, axis, line, position + radius + 1, length) - load(origin, axis, line, position - radius, length);
+    }
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
+fn compute_horizontal(@builtin(global_invocation_id) id: vec3<u32>) {
+    blur(i32(id.x), i32(id.y), vec2(1, 0));
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
+fn compute_vertical(@builtin(global_invocation_id) id: vec3<u32>) {
+    blur(i32(id.x), i32(id.y), vec2(0, 1));
+}
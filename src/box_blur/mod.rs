@@ -14,17 +14,43 @@ use bevy::{
     },
     prelude::*,
     render::{
-        extract_component::{ExtractComponentPlugin, UniformComponentPlugin},
+        camera::ExtractedCamera,
         render_graph::{RenderGraphApp, ViewNodeRunner},
-        RenderApp,
+        render_resource::{
+            DynamicUniformBuffer, SpecializedRenderPipelines, TextureDescriptor, TextureDimension,
+            TextureUsages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::{CachedTexture, TextureCache},
+        view::ViewTarget,
+        Render, RenderApp, RenderSet,
     },
 };
 use pipeline::*;
 
+use crate::{
+    alpha_mode::load_alpha_mode_shader,
+    bind_groups::ViewBindGroups,
+    compute::{is_compute_supported, COMPUTE_TEXTURE_FORMAT},
+    edge_mode::load_edge_mode_shader,
+    extract::ExtractBlurSettingsPlugin,
+    transition::animate_blur_transitions,
+    viewport::viewport_uv_rect,
+    BlurTransitionFinished,
+};
+
 use self::settings::BoxBlurUniforms;
 
 const BOX_BLUR_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(0xb95e014dc9aa489d8896aa486b01c666);
+const BOX_BLUR_COMPUTE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0xc2565497e9e4482ab95161d12908536e);
+
+// Number of lines (or columns) whose segments are scanned by a workgroup of the running sum compute shaders.
+const BOX_BLUR_WORKGROUP_SIZE: u32 = 64;
+// Number of pixels of the line segments scanned by each invocation of the running sum compute shaders,
+// the running sum being computed again at the start of each segment.
+const BOX_BLUR_SEGMENT_LENGTH: u32 = 128;
 
 /// This plugins adds support for a box blur post-processing effects to 2D or 3D cameras.
 ///
@@ -34,6 +60,8 @@ const BOX_BLUR_SHADER_HANDLE: Handle<Shader> =
 /// Additional details on blur filters can be found in this [Intel article](https://www.intel.com/content/www/us/en/developer/articles/technical/an-investigation-of-fast-real-time-gpu-based-image-blur-algorithms.html)
 ///
 /// This implementation is done with 2 post-processing passes.
+/// For big kernel sizes, those passes are done by compute shaders keeping a running sum of the kernel,
+/// whose cost barely depends on the kernel size.
 ///
 /// ```
 ///# use bevy::prelude::*;
@@ -59,18 +87,36 @@ impl Plugin for BoxBlurPlugin {
             "box_blur.wgsl",
             Shader::from_wgsl
         );
+        load_internal_asset!(
+            app,
+            BOX_BLUR_COMPUTE_SHADER_HANDLE,
+            "box_blur_compute.wgsl",
+            Shader::from_wgsl
+        );
         app.register_type::<BoxBlurSettings>();
+        load_edge_mode_shader(app);
+        load_alpha_mode_shader(app);
+        app.add_event::<BlurTransitionFinished>()
+            .add_systems(Update, animate_blur_transitions::<BoxBlurSettings>);
 
-        app.add_plugins((
-            ExtractComponentPlugin::<BoxBlurSettings>::default(),
-            UniformComponentPlugin::<BoxBlurUniforms>::default(),
-        ));
+        app.add_plugins((ExtractBlurSettingsPlugin::<BoxBlurSettings>::default(),));
 
         let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
         render_app
+            .init_resource::<BoxBlurUniformBuffer>()
+            .init_resource::<ViewBindGroups<BoxBlurPipeline>>()
+            .init_resource::<SpecializedRenderPipelines<BoxBlurPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_box_blur_pipelines.in_set(RenderSet::Prepare),
+                    prepare_box_blur_uniforms.in_set(RenderSet::PrepareResources),
+                    prepare_box_blur_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
             // Add box blur to the 3d render graph;
             .add_render_graph_node::<ViewNodeRunner<BoxBlurNode>>(Core3d, BoxBlurLabel)
             .add_render_graph_edges(
@@ -99,5 +145,94 @@ impl Plugin for BoxBlurPlugin {
         };
 
         render_app.init_resource::<BoxBlurPipeline>();
+        warm_up_box_blur_pipelines(&mut render_app.world);
+
+        // Without the compute pipeline, no view gets compute textures, and the fragment passes are used.
+        if is_compute_supported(&render_app.world, BOX_BLUR_WORKGROUP_SIZE) {
+            render_app
+                .init_resource::<BoxBlurComputePipeline>()
+                .init_resource::<ViewBindGroups<BoxBlurComputePipeline>>()
+                .add_systems(
+                    Render,
+                    (
+                        prepare_box_blur_compute_textures.in_set(RenderSet::PrepareResources),
+                        prepare_box_blur_compute_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                    ),
+                );
+        }
     }
 }
+
+// Uniforms of all the passes of all the views, each pass binding its own offset.
+#[derive(Resource, Default)]
+pub(crate) struct BoxBlurUniformBuffer(DynamicUniformBuffer<BoxBlurUniforms>);
+
+// Offsets in the `BoxBlurUniformBuffer` of each pass of a view, in the order of the passes.
+#[derive(Component)]
+pub(crate) struct BoxBlurUniformOffsets(Vec<u32>);
+
+fn prepare_box_blur_uniforms(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut uniform_buffer: ResMut<BoxBlurUniformBuffer>,
+    views: Query<(Entity, &BoxBlurSettings, &ExtractedCamera)>,
+) {
+    uniform_buffer.0.clear();
+    for (entity, settings, camera) in &views {
+        let viewport = viewport_uv_rect(camera.viewport.as_ref(), camera.physical_target_size);
+        let offsets = settings
+            .pass_kernel_sizes()
+            .into_iter()
+            .map(|kernel_size| {
+                uniform_buffer.0.push(&BoxBlurUniforms {
+                    viewport,
+                    kernel_size,
+                    sampling_distance_factor: settings.sampling_distance_factor,
+                    edge_mode: settings.edge_mode.shader_value(),
+                    alpha_mode: settings.alpha_mode.shader_value(),
+                })
+            })
+            .collect();
+        commands
+            .entity(entity)
+            .insert(BoxBlurUniformOffsets(offsets));
+    }
+    uniform_buffer.0.write_buffer(&render_device, &render_queue);
+}
+
+fn prepare_box_blur_compute_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(Entity, &ViewTarget, &BoxBlurSettings)>,
+) {
+    for (entity, view_target, settings) in &views {
+        if !settings.uses_compute_shader() {
+            continue;
+        }
+        let texture_descriptor = TextureDescriptor {
+            label: Some("box_blur_compute_texture"),
+            size: view_target.main_texture().size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: COMPUTE_TEXTURE_FORMAT,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        commands.entity(entity).insert(BoxBlurComputeTextures {
+            horizontal: texture_cache.get(&render_device, texture_descriptor.clone()),
+            vertical: texture_cache.get(&render_device, texture_descriptor),
+        });
+    }
+}
+
+// Storage textures written by the compute passes of a view.
+#[derive(Component)]
+pub(crate) struct BoxBlurComputeTextures {
+    // Written by the horizontal passes, read by the vertical passes.
+    horizontal: CachedTexture,
+    // Written by the vertical passes, read by the next horizontal pass, or copied to the view target.
+    vertical: CachedTexture,
+}
@@ -1,18 +1,26 @@
-use super::settings::BoxBlurUniforms;
-use super::BOX_BLUR_SHADER_HANDLE;
+use super::settings::{specialized_kernel_radius, BoxBlurUniforms};
+use super::{
+    BoxBlurComputeTextures, BoxBlurSettings, BoxBlurUniformBuffer, BoxBlurUniformOffsets,
+    BOX_BLUR_COMPUTE_SHADER_HANDLE, BOX_BLUR_SEGMENT_LENGTH, BOX_BLUR_SHADER_HANDLE,
+    BOX_BLUR_WORKGROUP_SIZE,
+};
+use crate::{bind_groups::ViewBindGroups, compute::COMPUTE_TEXTURE_FORMAT};
 use bevy::{
     core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
     ecs::query::QueryItem,
     prelude::*,
     render::{
-        extract_component::ComponentUniforms,
+        camera::ExtractedCamera,
         render_graph::{NodeRunError, RenderGraphContext, RenderLabel, ViewNode},
         render_resource::{
-            binding_types::{sampler, texture_2d, uniform_buffer},
-            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
-            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
-            SamplerBindingType, SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+            binding_types::{sampler, texture_2d, texture_storage_2d, uniform_buffer},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BindingResource,
+            CachedComputePipelineId, CachedRenderPipelineId, ComputePassDescriptor,
+            ComputePipelineDescriptor, FilterMode, FragmentState, MultisampleState, Operations,
+            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderDefVal,
+            ShaderStages, SpecializedRenderPipeline, SpecializedRenderPipelines,
+            StorageTextureAccess, TextureFormat, TextureSampleType, TextureView,
         },
         renderer::{RenderContext, RenderDevice},
         texture::BevyDefault,
@@ -31,25 +39,135 @@ pub(crate) struct BoxBlurNode;
 impl ViewNode for BoxBlurNode {
     type ViewQuery = (
         &'static ViewTarget,
-        // This make sure the node is only run on cameras with an extracted BoxBlurUniform component
-        &'static BoxBlurUniforms,
+        &'static ExtractedCamera,
+        // This make sure the node is only run on cameras with an extracted BoxBlurSettings component
+        &'static BoxBlurUniformOffsets,
+        // Only prepared for big kernel sizes, when compute shaders are supported
+        Option<&'static BoxBlurComputeTextures>,
+        &'static BoxBlurPipelineIds,
     );
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, box_blur_uniforms): QueryItem<Self::ViewQuery>,
+        (view_target, camera, uniform_offsets, compute_textures, pipeline_ids): QueryItem<
+            Self::ViewQuery,
+        >,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let box_blur_pipeline = world.resource::<BoxBlurPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
-        let settings_uniforms = world.resource::<ComponentUniforms<BoxBlurUniforms>>();
+        let bind_groups = world.resource::<ViewBindGroups<BoxBlurPipeline>>();
 
-        let (Some(settings_binding), Some(horizontal_pipeline), Some(vertical_pipeline)) = (
-            settings_uniforms.uniforms().binding(),
-            pipeline_cache.get_render_pipeline(box_blur_pipeline.horizontal_pipeline_id),
-            pipeline_cache.get_render_pipeline(box_blur_pipeline.vertical_pipeline_id),
-        ) else {
+        let Some(bind_groups) = bind_groups.get(graph.view_entity()) else {
+            return Ok(());
+        };
+
+        // The fragment passes are used while the compute pipelines are not ready.
+        if let Some(compute_textures) = compute_textures {
+            let compute_pipeline = world.resource::<BoxBlurComputePipeline>();
+            let compute_bind_groups = world.resource::<ViewBindGroups<BoxBlurComputePipeline>>();
+            if let (
+                Some(compute_bind_groups),
+                Some(horizontal_pipeline),
+                Some(vertical_pipeline),
+                Some(copy_pipeline),
+            ) = (
+                compute_bind_groups.get(graph.view_entity()),
+                pipeline_cache.get_compute_pipeline(compute_pipeline.horizontal_pipeline_id),
+                pipeline_cache.get_compute_pipeline(compute_pipeline.vertical_pipeline_id),
+                pipeline_cache.get_render_pipeline(box_blur_pipeline.copy_pipeline_id),
+            ) {
+                render_context
+                    .command_encoder()
+                    .push_debug_group("box_blur");
+
+                let post_process = view_target.post_process_write();
+                // Only the viewport of the textures is blurred.
+                let size = camera.physical_viewport_size.unwrap_or(UVec2::new(
+                    compute_textures.horizontal.texture.width(),
+                    compute_textures.horizontal.texture.height(),
+                ));
+                {
+                    let mut compute_pass = render_context.command_encoder().begin_compute_pass(
+                        &ComputePassDescriptor {
+                            label: Some("box_blur_compute_pass"),
+                            timestamp_writes: None,
+                        },
+                    );
+                    // The first pass reads the view target, the next ones the result of the previous pass.
+                    // Each pass binds the uniforms prepared for its own kernel size.
+                    let mut source = post_process.source;
+                    for &uniform_offset in uniform_offsets.0.iter() {
+                        // Each invocation scans a segment of a line, then of a column.
+                        compute_pass.set_pipeline(horizontal_pipeline);
+                        compute_pass.set_bind_group(
+                            0,
+                            compute_bind_groups.get(source),
+                            &[uniform_offset],
+                        );
+                        compute_pass.dispatch_workgroups(
+                            size.y.div_ceil(BOX_BLUR_WORKGROUP_SIZE),
+                            size.x.div_ceil(BOX_BLUR_SEGMENT_LENGTH),
+                            1,
+                        );
+                        compute_pass.set_pipeline(vertical_pipeline);
+                        compute_pass.set_bind_group(
+                            0,
+                            compute_bind_groups.get(&compute_textures.horizontal.default_view),
+                            &[uniform_offset],
+                        );
+                        compute_pass.dispatch_workgroups(
+                            size.x.div_ceil(BOX_BLUR_WORKGROUP_SIZE),
+                            size.y.div_ceil(BOX_BLUR_SEGMENT_LENGTH),
+                            1,
+                        );
+                        source = &compute_textures.vertical.default_view;
+                    }
+                }
+
+                // The storage texture is copied to the view target.
+                let mut render_pass =
+                    render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                        label: Some("box_blur_copy_pass"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: post_process.destination,
+                            resolve_target: None,
+                            ops: Operations::default(),
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                if let Some(viewport) = &camera.viewport {
+                    render_pass.set_camera_viewport(viewport);
+                }
+                render_pass.set_render_pipeline(copy_pipeline);
+                // The copy only reads the viewport, the same in the uniforms of every pass of the view.
+                render_pass.set_bind_group(
+                    0,
+                    bind_groups.get(&compute_textures.vertical.default_view),
+                    &[uniform_offsets.0[0]],
+                );
+                render_pass.draw(0..3, 0..1);
+                drop(render_pass);
+
+                render_context.command_encoder().pop_debug_group();
+
+                return Ok(());
+            }
+        }
+
+        // The pipelines of all the passes must be ready before the first one is done.
+        let Some(pass_pipelines) = pipeline_ids
+            .0
+            .iter()
+            .map(|ids| {
+                let [horizontal, vertical] = ids.map(|id| pipeline_cache.get_render_pipeline(id));
+                Some([horizontal?, vertical?])
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
             return Ok(());
         };
 
@@ -57,23 +175,12 @@ impl ViewNode for BoxBlurNode {
             .command_encoder()
             .push_debug_group("box_blur");
 
-        for _ in 0..box_blur_uniforms.passes {
-            for pipeline in [horizontal_pipeline, vertical_pipeline] {
+        // Each pass binds the uniforms prepared for its own kernel size.
+        for (pipelines, &uniform_offset) in pass_pipelines.iter().zip(uniform_offsets.0.iter()) {
+            for pipeline in pipelines {
                 let post_process = view_target.post_process_write();
 
-                let bind_group = render_context.render_device().create_bind_group(
-                    "box_blur_bind_group",
-                    &box_blur_pipeline.layout,
-                    // It's important for this to match the BindGroupLayout defined in the BoxBlurPipeline
-                    &BindGroupEntries::sequential((
-                        // Make sure to use the source view
-                        post_process.source,
-                        // Use the sampler created for the pipeline
-                        &box_blur_pipeline.sampler,
-                        // Set the settings binding
-                        settings_binding.clone(),
-                    )),
-                );
+                let bind_group = bind_groups.get(post_process.source);
 
                 let mut render_pass =
                     render_context.begin_tracked_render_pass(RenderPassDescriptor {
@@ -90,8 +197,12 @@ impl ViewNode for BoxBlurNode {
                         occlusion_query_set: None,
                     });
 
+                // The other cameras sharing the render target keep their viewports.
+                if let Some(viewport) = &camera.viewport {
+                    render_pass.set_camera_viewport(viewport);
+                }
                 render_pass.set_render_pipeline(pipeline);
-                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.set_bind_group(0, bind_group, &[uniform_offset]);
                 render_pass.draw(0..3, 0..1);
             }
         }
@@ -103,13 +214,34 @@ impl ViewNode for BoxBlurNode {
 }
 
 #[derive(Resource)]
-pub struct BoxBlurPipeline {
+pub(crate) struct BoxBlurPipeline {
     layout: BindGroupLayout,
     sampler: Sampler,
-    horizontal_pipeline_id: CachedRenderPipelineId,
-    vertical_pipeline_id: CachedRenderPipelineId,
+    copy_pipeline_id: CachedRenderPipelineId,
+}
+
+// The fragment passes are specialized on their direction and on the kernel radius,
+// the loop on the kernel having a constant bound.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct BoxBlurPipelineKey {
+    vertical: bool,
+    // Quantized kernel radius, the additional pixels having a null weight.
+    kernel_radius: u32,
+}
+
+impl BoxBlurPipelineKey {
+    fn new(vertical: bool, kernel_size: u32) -> Self {
+        Self {
+            vertical,
+            kernel_radius: specialized_kernel_radius(kernel_size),
+        }
+    }
 }
 
+// Specialized pipelines of the fragment passes of a view, horizontal then vertical, for each pass.
+#[derive(Component)]
+pub(crate) struct BoxBlurPipelineIds(Vec<[CachedRenderPipelineId; 2]>);
+
 impl FromWorld for BoxBlurPipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
@@ -121,66 +253,280 @@ impl FromWorld for BoxBlurPipeline {
                 (
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
-                    uniform_buffer::<BoxBlurUniforms>(false),
+                    uniform_buffer::<BoxBlurUniforms>(true),
                 ),
             ),
         );
 
-        // We can create the sampler here since it won't change at runtime and doesn't depend on the view
-        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
-
-        let horizontal_pipeline_id = world
-            .resource_mut::<PipelineCache>()
-            // This will add the pipeline to the cache and queue it's creation
-            .queue_render_pipeline(RenderPipelineDescriptor {
-                label: Some("box_blur_horizontal_pipeline".into()),
-                layout: vec![layout.clone()],
-                // This will setup a fullscreen triangle for the vertex state
-                vertex: fullscreen_shader_vertex_state(),
-                fragment: Some(FragmentState {
-                    shader: BOX_BLUR_SHADER_HANDLE,
-                    shader_defs: vec![],
-                    // Make sure this matches the entry point of your shader.
-                    // It can be anything as long as it matches here and in the shader.
-                    entry_point: "fragment_horizontal".into(),
-                    targets: vec![Some(TextureFormat::bevy_default().into())],
-                }),
-                // All of the following properties are not important for this effect so just use the default values.
-                // This struct doesn't have the Default trait implemented because not all field can have a default value.
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                push_constant_ranges: vec![],
-            });
-        let vertical_pipeline_id = world
-            .resource_mut::<PipelineCache>()
-            // This will add the pipeline to the cache and queue it's creation
-            .queue_render_pipeline(RenderPipelineDescriptor {
-                label: Some("box_blur_vertical_pipeline".into()),
-                layout: vec![layout.clone()],
-                // This will setup a fullscreen triangle for the vertex state
-                vertex: fullscreen_shader_vertex_state(),
-                fragment: Some(FragmentState {
-                    shader: BOX_BLUR_SHADER_HANDLE,
-                    shader_defs: vec![],
-                    // Make sure this matches the entry point of your shader.
-                    // It can be anything as long as it matches here and in the shader.
-                    entry_point: "fragment_vertical".into(),
-                    targets: vec![Some(TextureFormat::bevy_default().into())],
-                }),
-                // All of the following properties are not important for this effect so just use the default values.
-                // This struct doesn't have the Default trait implemented because not all field can have a default value.
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                push_constant_ranges: vec![],
-            });
+        // We can create the sampler here since it won't change at runtime and doesn't depend on the view.
+        // Linear filtering is used for non integer sampling distance factors.
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        // Copies the result of the compute shaders to the view target.
+        let copy_pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("box_blur_copy_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: BOX_BLUR_SHADER_HANDLE,
+                        shader_defs: vec![],
+                        entry_point: "fragment_copy".into(),
+                        targets: vec![Some(TextureFormat::bevy_default().into())],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                });
 
         Self {
             layout,
             sampler,
+            copy_pipeline_id,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for BoxBlurPipeline {
+    type Key = BoxBlurPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let (label, entry_point) = if key.vertical {
+            ("box_blur_vertical_pipeline", "fragment_vertical")
+        } else {
+            ("box_blur_horizontal_pipeline", "fragment_horizontal")
+        };
+        RenderPipelineDescriptor {
+            label: Some(label.into()),
+            layout: vec![self.layout.clone()],
+            // This will setup a fullscreen triangle for the vertex state
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: BOX_BLUR_SHADER_HANDLE,
+                shader_defs: vec![ShaderDefVal::UInt(
+                    "KERNEL_RADIUS".into(),
+                    key.kernel_radius,
+                )],
+                // Make sure this matches the entry point of your shader.
+                // It can be anything as long as it matches here and in the shader.
+                entry_point: entry_point.into(),
+                targets: vec![Some(TextureFormat::bevy_default().into())],
+            }),
+            // All of the following properties are not important for this effect so just use the default values.
+            // This struct doesn't have the Default trait implemented because not all field can have a default value.
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+pub(crate) fn prepare_box_blur_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    box_blur_pipeline: Res<BoxBlurPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BoxBlurPipeline>>,
+    views: Query<(Entity, &BoxBlurSettings)>,
+) {
+    for (entity, settings) in &views {
+        let pass_pipelines = settings
+            .pass_kernel_sizes()
+            .into_iter()
+            .map(|kernel_size| {
+                [false, true].map(|vertical| {
+                    pipelines.specialize(
+                        &pipeline_cache,
+                        &box_blur_pipeline,
+                        BoxBlurPipelineKey::new(vertical, kernel_size),
+                    )
+                })
+            })
+            .collect();
+        commands
+            .entity(entity)
+            .insert(BoxBlurPipelineIds(pass_pipelines));
+    }
+}
+
+// Queues the pipelines of the settings for common sigmas, so that they are compiled before being used.
+pub(crate) fn warm_up_box_blur_pipelines(world: &mut World) {
+    world.resource_scope(
+        |world, mut pipelines: Mut<SpecializedRenderPipelines<BoxBlurPipeline>>| {
+            let pipeline_cache = world.resource::<PipelineCache>();
+            let box_blur_pipeline = world.resource::<BoxBlurPipeline>();
+            let mut kernel_sizes: Vec<u32> = [2.0, 5.0, 10.0, 20.0]
+                .into_iter()
+                .flat_map(|sigma| BoxBlurSettings::from_sigma(sigma).pass_kernel_sizes())
+                .chain([BoxBlurSettings::default().kernel_size])
+                .collect();
+            kernel_sizes.sort_unstable();
+            kernel_sizes.dedup();
+            for kernel_size in kernel_sizes {
+                for vertical in [false, true] {
+                    pipelines.specialize(
+                        pipeline_cache,
+                        box_blur_pipeline,
+                        BoxBlurPipelineKey::new(vertical, kernel_size),
+                    );
+                }
+            }
+        },
+    );
+}
+
+impl BoxBlurPipeline {
+    // Creates the bind group of a pass sampling `source`.
+    fn create_bind_group(
+        &self,
+        render_device: &RenderDevice,
+        source: &TextureView,
+        settings_binding: BindingResource,
+    ) -> BindGroup {
+        render_device.create_bind_group(
+            "box_blur_bind_group",
+            &self.layout,
+            // It's important for this to match the BindGroupLayout defined in the BoxBlurPipeline
+            &BindGroupEntries::sequential((
+                // Make sure to use the source view
+                source,
+                // Use the sampler created for the pipeline
+                &self.sampler,
+                // Set the settings binding
+                settings_binding,
+            )),
+        )
+    }
+}
+
+// Each pass samples one of the main textures of the view, a bind group is prepared for both of them.
+// With the compute shaders, the copy pass samples the texture written by the last vertical pass.
+pub(crate) fn prepare_box_blur_bind_groups(
+    render_device: Res<RenderDevice>,
+    box_blur_pipeline: Res<BoxBlurPipeline>,
+    uniform_buffer: Res<BoxBlurUniformBuffer>,
+    mut bind_groups: ResMut<ViewBindGroups<BoxBlurPipeline>>,
+    views: Query<
+        (Entity, &ViewTarget, Option<&BoxBlurComputeTextures>),
+        With<BoxBlurUniformOffsets>,
+    >,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) =
+        (uniform_buffer.0.buffer(), uniform_buffer.0.binding())
+    else {
+        return;
+    };
+    for (view, view_target, compute_textures) in &views {
+        let mut sources = vec![
+            view_target.main_texture_view(),
+            view_target.main_texture_other_view(),
+        ];
+        sources.extend(compute_textures.map(|textures| &textures.vertical.default_view));
+        bind_groups.update(view, &sources, Some(buffer.id()), |source| {
+            box_blur_pipeline.create_bind_group(&render_device, source, settings_binding.clone())
+        });
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct BoxBlurComputePipeline {
+    layout: BindGroupLayout,
+    horizontal_pipeline_id: CachedComputePipelineId,
+    vertical_pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for BoxBlurComputePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "box_blur_compute_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    texture_storage_2d(COMPUTE_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly),
+                    uniform_buffer::<BoxBlurUniforms>(true),
+                ),
+            ),
+        );
+
+        let shader_defs = vec![
+            ShaderDefVal::UInt("WORKGROUP_SIZE".into(), BOX_BLUR_WORKGROUP_SIZE),
+            ShaderDefVal::Int("SEGMENT_LENGTH".into(), BOX_BLUR_SEGMENT_LENGTH as i32),
+        ];
+        let mut queue_pipeline = |label: &'static str, entry_point: &'static str| {
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some(label.into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: BOX_BLUR_COMPUTE_SHADER_HANDLE,
+                    shader_defs: shader_defs.clone(),
+                    entry_point: entry_point.into(),
+                })
+        };
+        let horizontal_pipeline_id =
+            queue_pipeline("box_blur_compute_horizontal_pipeline", "compute_horizontal");
+        let vertical_pipeline_id =
+            queue_pipeline("box_blur_compute_vertical_pipeline", "compute_vertical");
+
+        Self {
+            layout,
             horizontal_pipeline_id,
             vertical_pipeline_id,
         }
     }
 }
+
+// Horizontal passes read one of the main textures, or the result of the previous vertical pass,
+// and write to the horizontal texture, read by the vertical passes writing to the vertical texture.
+pub(crate) fn prepare_box_blur_compute_bind_groups(
+    render_device: Res<RenderDevice>,
+    compute_pipeline: Res<BoxBlurComputePipeline>,
+    uniform_buffer: Res<BoxBlurUniformBuffer>,
+    mut bind_groups: ResMut<ViewBindGroups<BoxBlurComputePipeline>>,
+    views: Query<(Entity, &ViewTarget, &BoxBlurComputeTextures)>,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) =
+        (uniform_buffer.0.buffer(), uniform_buffer.0.binding())
+    else {
+        return;
+    };
+    for (view, view_target, textures) in &views {
+        let horizontal = &textures.horizontal.default_view;
+        let vertical = &textures.vertical.default_view;
+        bind_groups.update(
+            view,
+            &[
+                view_target.main_texture_view(),
+                view_target.main_texture_other_view(),
+                horizontal,
+                vertical,
+            ],
+            Some(buffer.id()),
+            |source| {
+                let output = if source.id() == horizontal.id() {
+                    vertical
+                } else {
+                    horizontal
+                };
+                render_device.create_bind_group(
+                    "box_blur_compute_bind_group",
+                    &compute_pipeline.layout,
+                    &BindGroupEntries::sequential((source, output, settings_binding.clone())),
+                )
+            },
+        );
+    }
+}
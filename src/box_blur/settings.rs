@@ -1,10 +1,11 @@
-use bevy::ecs::query::QueryItem;
 use bevy::prelude::*;
-use bevy::render::{extract_component::ExtractComponent, render_resource::ShaderType};
+use bevy::render::render_resource::ShaderType;
+
+use crate::{extract::ExtractBlurSettings, BlurAlphaMode, BlurEdgeMode, BlurQuality, BlurSetting};
 
 /// Applies a box blur effect to a 2d or 3d camera in conjonction with the [`GaussianBlurPlugin`]
 ///
-#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
 #[reflect(Component, Default)]
 pub struct BoxBlurSettings {
     /// Kernel size for the computation of the box blur
@@ -13,21 +14,52 @@ pub struct BoxBlurSettings {
     /// - A value of 1 disable the blurring effect.
     /// - Defaults to 21.
     ///
-    /// The computational cost of the box blur post-processing effect is `2*kernel_size*passes` texture sampling per pixels.
+    /// The computational cost of the box blur post-processing effect is about `2*kernel_size*passes` texture sampling per pixels.
+    /// From a kernel size of 33, compute shaders keeping a running sum of the kernel are used instead when supported,
+    /// for a cost of about `(4+kernel_size/64)*passes+1` texture sampling per pixels.
     pub kernel_size: u32,
     /// Defines the number of time the box convolution is apply successively.
     /// Multiple passes increases the quality of the blur and reduce the 'box' artefacts.
     /// - This value will be clamped to the range [1..5]
     /// - Defaults to 2
     ///
-    /// The computational cost of the box blur post-processing effect is `2*kernel_size*passes` texture sampling per pixels.
+    /// Each pass adds the cost of a kernel size, see [`BlurSetting::sampling_per_pixel`](crate::BlurSetting::sampling_per_pixel).
     pub passes: u32,
+    /// Kernel size of each consecutive pass, replacing `kernel_size` and `passes` when not empty.
+    /// Using different kernel sizes for each pass gives a closer approximation of a gaussian blur,
+    /// see [`BoxBlurSettings::from_sigma`].
+    /// - Each value is made legal as `kernel_size` is, and only the first 5 values are used.
+    /// - Passes with a kernel size of 1 are skipped.
+    /// - Defaults to an empty list.
+    pub kernel_sizes: Vec<u32>,
+    /// A factor that is applied whenever the post-processing effect is sampling for a distant pixel.
+    /// This can be used to create a bigger blur without impacting the computational cost, but sacrificing quality.
+    /// - It will be clamped to the range [1..100]
+    /// - Non integer values are supported, this will take advantage of linear filtering.
+    /// - Defaults to 1, which is the neutral value, not impacting the algorithm.
+    ///
+    /// The running sum compute shaders are only used when it is 1.
+    pub sampling_distance_factor: f32,
+    /// Defines how the pixels beyond the edges of the view are sampled.
+    /// - Defaults to [`BlurEdgeMode::Clamp`]
+    ///
+    /// The running sum compute shaders are only used with [`BlurEdgeMode::Clamp`].
+    pub edge_mode: BlurEdgeMode,
+    /// Defines how the alpha channel is blurred, for cameras rendering to a transparent target.
+    /// - Defaults to [`BlurAlphaMode::Straight`]
+    ///
+    /// The running sum compute shaders are only used with [`BlurAlphaMode::Straight`].
+    pub alpha_mode: BlurAlphaMode,
 }
 impl Default for BoxBlurSettings {
     fn default() -> Self {
         Self {
             kernel_size: 21,
             passes: 2,
+            kernel_sizes: vec![],
+            sampling_distance_factor: 1.0,
+            edge_mode: BlurEdgeMode::Clamp,
+            alpha_mode: BlurAlphaMode::Straight,
         }
     }
 }
@@ -35,62 +67,334 @@ impl crate::BlurSetting for BoxBlurSettings {
     const NO_BLUR: BoxBlurSettings = BoxBlurSettings {
         kernel_size: 1,
         passes: 1,
+        kernel_sizes: vec![],
+        sampling_distance_factor: 1.0,
+        edge_mode: BlurEdgeMode::Clamp,
+        alpha_mode: BlurAlphaMode::Straight,
     };
 
     fn sampling_per_pixel(&self) -> f32 {
-        match self.kernel_size {
-            1 => 0.0,
-            k => (2 * k * self.passes) as f32,
+        let settings = self.create_concrete();
+        let kernel_sizes = settings.pass_kernel_sizes();
+        if kernel_sizes.is_empty() {
+            0.0
+        } else if settings.uses_compute_shader() {
+            // Each pixel enters and leaves the running sum of both directions,
+            // the whole kernel being summed at the start of each segment,
+            // then the result is copied to the view target.
+            let segment_length = super::BOX_BLUR_SEGMENT_LENGTH as f32;
+            kernel_sizes
+                .iter()
+                .map(|&k| 2.0 * (2.0 + k as f32 / segment_length))
+                .sum::<f32>()
+                + 1.0
+        } else {
+            kernel_sizes
+                .iter()
+                .map(|&k| (2 * (2 * specialized_kernel_radius(k) + 1)) as f32)
+                .sum()
         }
     }
 
     fn passes(&self) -> u32 {
-        match self.kernel_size {
-            1 => 0,
-            _ => self.passes,
+        self.create_concrete().pass_kernel_sizes().len() as u32
+    }
+
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64 {
+        let pixels = viewport_size.x as f64 * viewport_size.y as f64;
+        (pixels * self.sampling_per_pixel() as f64).round() as u64
+    }
+
+    fn equivalent_sigma(&self) -> f32 {
+        let settings = self.create_concrete();
+        let variance: f32 = settings
+            .pass_kernel_sizes()
+            .into_iter()
+            .map(box_variance)
+            .sum();
+        variance.sqrt() * settings.sampling_distance_factor
+    }
+
+    /// The kernel sizes of each pass are interpolated as [`KawaseBlurSettings`](crate::KawaseBlurSettings)
+    /// sampling distances are, a missing pass having a kernel size of 1.
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let (start, end) = (self.create_concrete(), other.create_concrete());
+        let kernel_sizes = if start.kernel_sizes.is_empty() && end.kernel_sizes.is_empty() {
+            vec![]
+        } else {
+            let (start_sizes, end_sizes) = (start.pass_kernel_sizes(), end.pass_kernel_sizes());
+            let passes =
+                crate::lerp_u32(start_sizes.len() as u32, end_sizes.len() as u32, t) as usize;
+            (0..passes)
+                .map(|i| {
+                    let k1 = start_sizes.get(i).copied().unwrap_or(1);
+                    let k2 = end_sizes.get(i).copied().unwrap_or(1);
+                    crate::lerp_kernel_size(k1, k2, t)
+                })
+                .collect()
+        };
+        BoxBlurSettings {
+            kernel_size: crate::lerp_kernel_size(start.kernel_size, end.kernel_size, t),
+            passes: crate::lerp_u32(start.passes, end.passes, t),
+            kernel_sizes,
+            sampling_distance_factor: start.sampling_distance_factor
+                + (end.sampling_distance_factor - start.sampling_distance_factor) * t,
+            edge_mode: if t < 0.5 {
+                self.edge_mode
+            } else {
+                other.edge_mode
+            },
+            alpha_mode: if t < 0.5 {
+                self.alpha_mode
+            } else {
+                other.alpha_mode
+            },
         }
+        .create_concrete()
     }
 }
 impl BoxBlurSettings {
-    /// Computes a new `BoxBlurSettings` where each attribute is legal as expected by the shader.
-    pub fn create_concrete(&self) -> BoxBlurSettings {
-        let mut kernel_size = self.kernel_size.clamp(1, 401);
-        if kernel_size % 2 == 0 {
-            kernel_size += 1;
-        }
-        let passes = self.passes.clamp(1, 5);
+    /// Computes the `BoxBlurSettings` that best approximates a gaussian blur of the given `sigma`.
+    ///
+    /// It uses 3 passes, or 5 for very large `sigma`, with the kernel sizes below and above the optimal box width,
+    /// see [`BoxBlurSettings::from_sigma_with_kernel_sizes`].
+    ///
+    /// ```
+    ///# use bevy_camera_blur::*;
+    /// let settings = BoxBlurSettings::from_sigma(3.2);
+    /// assert_eq!(settings.kernel_sizes, vec![5, 7, 7]);
+    /// ```
+    pub fn from_sigma(sigma: f32) -> BoxBlurSettings {
+        let passes = if box_variance(401) * 3.0 < sigma.powi(2) {
+            5
+        } else {
+            3
+        };
+        BoxBlurSettings::from_sigma_with_kernel_sizes(sigma, passes)
+    }
+
+    /// Computes the `BoxBlurSettings` that best approximates a gaussian blur of the given `sigma`
+    /// using a fixed number of `passes` of the same kernel size.
+    ///
+    /// It selects the odd kernel size around the optimal box width described by Peter Kovesi in
+    /// [Fast Almost-Gaussian Filtering](https://www.peterkovesi.com/papers/FastGaussianSmoothing.pdf)
+    /// whose variance is the closest to the gaussian one.
+    pub fn from_sigma_with_passes(sigma: f32, passes: u32) -> BoxBlurSettings {
+        let variance = sigma.max(0.0).powi(2);
+        let passes = passes.clamp(1, 5);
+        let ideal_width = (12.0 * variance / passes as f32 + 1.0).sqrt();
+        let lower = match ideal_width.floor() as u32 {
+            w if w.is_multiple_of(2) => w.saturating_sub(1).max(1),
+            w => w,
+        };
+        let kernel_size = [lower, lower + 2]
+            .into_iter()
+            .map(|w| w.min(401))
+            .min_by(|&w1, &w2| {
+                let error = |w| (passes as f32 * box_variance(w) - variance).abs();
+                error(w1).total_cmp(&error(w2))
+            })
+            .unwrap();
         BoxBlurSettings {
             kernel_size,
             passes,
+            ..default()
+        }
+    }
+
+    /// Computes the `BoxBlurSettings` with a kernel size for each pass that best approximates
+    /// a gaussian blur of the given `sigma` using a fixed number of `passes`.
+    ///
+    /// As described by Peter Kovesi in
+    /// [Fast Almost-Gaussian Filtering](https://www.peterkovesi.com/papers/FastGaussianSmoothing.pdf),
+    /// the first passes use the odd kernel size below the ideal box width, and the last ones the odd kernel size above it,
+    /// the number of each being chosen to match the variance of the gaussian blur.
+    ///
+    /// ```
+    ///# use bevy_camera_blur::*;
+    /// let settings = BoxBlurSettings::from_sigma_with_kernel_sizes(3.2, 3);
+    /// assert_eq!(settings.kernel_sizes, vec![5, 7, 7]);
+    /// assert!((settings.equivalent_sigma() - 3.2).abs() < 0.1);
+    /// ```
+    pub fn from_sigma_with_kernel_sizes(sigma: f32, passes: u32) -> BoxBlurSettings {
+        let variance = sigma.max(0.0).powi(2);
+        let passes = passes.clamp(1, 5);
+        let ideal_width = (12.0 * variance / passes as f32 + 1.0).sqrt();
+        let lower = match ideal_width.floor() as u32 {
+            w if w.is_multiple_of(2) => w.saturating_sub(1).max(1),
+            w => w,
+        }
+        .min(399);
+        let upper = lower + 2;
+        // Number of passes using the lower kernel size, whose variance is the closest to the gaussian one.
+        let lower_passes = (0..=passes)
+            .min_by(|&m1, &m2| {
+                let error = |m: u32| {
+                    (m as f32 * box_variance(lower) + (passes - m) as f32 * box_variance(upper)
+                        - variance)
+                        .abs()
+                };
+                error(m1).total_cmp(&error(m2))
+            })
+            .unwrap();
+        let kernel_sizes = (0..passes)
+            .map(|pass| if pass < lower_passes { lower } else { upper })
+            .collect();
+        BoxBlurSettings {
+            kernel_sizes,
+            ..default()
         }
     }
+
+    /// Computes the settings used for a [`BlurQuality`] by the default [`BlurQualityMapping`](crate::BlurQualityMapping),
+    /// with about the same equivalent sigma.
+    ///
+    /// - [`BlurQuality::Low`] and [`BlurQuality::Medium`] divide the kernel sizes by 3 and 1.5,
+    ///   multiplying the `sampling_distance_factor` accordingly.
+    /// - [`BlurQuality::High`] keeps the settings.
+    /// - [`BlurQuality::Ultra`] uses 5 passes, see [`BoxBlurSettings::from_sigma_with_kernel_sizes`].
+    pub fn with_quality(&self, quality: BlurQuality) -> BoxBlurSettings {
+        let spacing = match quality {
+            BlurQuality::Low => 3.0,
+            BlurQuality::Medium => 1.5,
+            BlurQuality::High => return self.clone(),
+            BlurQuality::Ultra => {
+                return BoxBlurSettings {
+                    edge_mode: self.edge_mode,
+                    alpha_mode: self.alpha_mode,
+                    ..BoxBlurSettings::from_sigma_with_kernel_sizes(self.equivalent_sigma(), 5)
+                }
+            }
+        };
+        let settings = self.create_concrete();
+        BoxBlurSettings {
+            kernel_size: crate::scale_kernel_size(settings.kernel_size, 1.0 / spacing),
+            kernel_sizes: settings
+                .kernel_sizes
+                .iter()
+                .map(|&kernel_size| crate::scale_kernel_size(kernel_size, 1.0 / spacing))
+                .collect(),
+            sampling_distance_factor: settings.sampling_distance_factor * spacing,
+            ..settings
+        }
+    }
+
+    /// Computes a new `BoxBlurSettings` where each attribute is legal as expected by the shader.
+    pub fn create_concrete(&self) -> BoxBlurSettings {
+        BoxBlurSettings {
+            kernel_size: legal_kernel_size(self.kernel_size),
+            passes: self.passes.clamp(1, 5),
+            kernel_sizes: self
+                .kernel_sizes
+                .iter()
+                .take(5)
+                .map(|&kernel_size| legal_kernel_size(kernel_size))
+                .collect(),
+            sampling_distance_factor: self.sampling_distance_factor.clamp(1.0, 100.0),
+            edge_mode: self.edge_mode,
+            alpha_mode: self.alpha_mode,
+        }
+    }
+
+    // Kernel size of each pass actually done, for concrete settings.
+    pub(crate) fn pass_kernel_sizes(&self) -> Vec<u32> {
+        let kernel_sizes = if self.kernel_sizes.is_empty() {
+            vec![self.kernel_size; self.passes as usize]
+        } else {
+            self.kernel_sizes.clone()
+        };
+        kernel_sizes.into_iter().filter(|&k| k > 1).collect()
+    }
+
+    // Whether the running sum compute shaders can be used for these concrete settings, when supported by the GPU.
+    pub(crate) fn uses_compute_shader(&self) -> bool {
+        self.sampling_distance_factor == 1.0
+            && self.edge_mode == BlurEdgeMode::Clamp
+            && self.alpha_mode == BlurAlphaMode::Straight
+            && self
+                .pass_kernel_sizes()
+                .iter()
+                .any(|&k| k >= RUNNING_SUM_MIN_KERNEL_SIZE)
+    }
+}
+
+// Clamps `kernel_size` to the range `[1..401]`, replacing an even value by the next odd one.
+fn legal_kernel_size(kernel_size: u32) -> u32 {
+    let kernel_size = kernel_size.clamp(1, 401);
+    if kernel_size.is_multiple_of(2) {
+        kernel_size + 1
+    } else {
+        kernel_size
+    }
+}
+
+// Kernel radius of the fragment pipelines specialized for `kernel_size`, the additional pixels having a null weight.
+pub(crate) fn specialized_kernel_radius(kernel_size: u32) -> u32 {
+    // Highest kernel radius, for a kernel size of 401.
+    crate::quantize_loop_count(kernel_size / 2).min(200)
+}
+
+// Kernel size from which the running sum compute shaders are used, when supported: the fragment passes then sample
+// 66 texels per pixel and pass, over ten times the compute ones, outweighing their copy pass and serial scans.
+pub(crate) const RUNNING_SUM_MIN_KERNEL_SIZE: u32 = 33;
+
+// Variance, in pixels, of one pass of a box blur of size `kernel_size`.
+fn box_variance(kernel_size: u32) -> f32 {
+    (kernel_size * kernel_size - 1) as f32 / 12.0
 }
 
-impl ExtractComponent for BoxBlurSettings {
-    type QueryData = &'static Self;
+impl ExtractBlurSettings for BoxBlurSettings {
+    type Out = BoxBlurSettings;
+
+    // The kernels grow up to the highest kernel size, the sampling distance factor growing beyond.
+    fn scaled(&self, factor: f32) -> Self {
+        let settings = self.create_concrete();
+        let radius = settings.pass_kernel_sizes().into_iter().max().unwrap_or(1) / 2;
+        if radius == 0 {
+            return settings;
+        }
+        let kernel_scale = factor.min(200.0 / radius as f32);
+        BoxBlurSettings {
+            kernel_size: crate::scale_kernel_size(settings.kernel_size, kernel_scale),
+            kernel_sizes: settings
+                .kernel_sizes
+                .iter()
+                .map(|&kernel_size| crate::scale_kernel_size(kernel_size, kernel_scale))
+                .collect(),
+            sampling_distance_factor: settings.sampling_distance_factor * factor / kernel_scale,
+            ..settings
+        }
+    }
+
+    fn map_quality(&self, quality: BlurQuality) -> Self {
+        self.with_quality(quality)
+    }
 
-    type QueryFilter = ();
-    type Out = BoxBlurUniforms;
+    fn capped(&self, max_sigma: f32) -> Self {
+        crate::extract::capped_to_sigma(self, max_sigma)
+    }
 
-    fn extract_component(settings: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
-        let settings = settings.create_concrete();
-        if settings.kernel_size == 1 {
+    fn extract(&self, _camera: &Camera) -> Option<Self::Out> {
+        let settings = self.create_concrete();
+        if settings.pass_kernel_sizes().is_empty() {
             None
         } else {
-            Some(BoxBlurUniforms {
-                kernel_size: settings.kernel_size,
-                passes: settings.passes,
-                _webgl2_padding: Vec2::ZERO,
-            })
+            Some(settings)
         }
     }
 }
 
-/// The uniform struct extracted from [`BoxBlurSettings`] attached to a Camera.
-/// Will be available for use in the box blur shader.
-#[derive(Component, ShaderType, Clone)]
+/// Data provided as Uniform for the shader, for each pass of the box blur.
+#[derive(ShaderType, Clone, Default)]
 pub struct BoxBlurUniforms {
+    // UV rectangle of the viewport of the camera in the view target
+    pub viewport: Vec4,
+    // Legalized kernel size of the current pass.
     pub kernel_size: u32,
-    pub passes: u32,
-    pub _webgl2_padding: Vec2,
+    // Legalized sampling_distance_factor
+    pub sampling_distance_factor: f32,
+    // Value of the legalized edge_mode
+    pub edge_mode: u32,
+    // Value of the alpha_mode
+    pub alpha_mode: u32,
 }
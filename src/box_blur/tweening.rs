@@ -1,15 +1,15 @@
 #![cfg(feature = "bevy_tweening")]
 use bevy_tweening::Lens;
 
-use crate::BoxBlurSettings;
+use crate::{BlurSetting, BoxBlurSettings};
 
 /// A `bevy_tweening` Lens implementation to allow animation of the box blur.
 ///
-/// This will tweens the `sigma`, `kernel_size`, and `texel_step` attributes
-/// of the [`BoxBlurSettings`].
+/// This will tweens the `kernel_size`, `passes`, `kernel_sizes` and `sampling_distance_factor` attributes
+/// of the [`BoxBlurSettings`], see [`BlurSetting::interpolate`].
 /// # Example
 ///
-/// System that will create an animation from a no blur to a  10-sigma blur effect
+/// System that will create an animation from a no blur to the default blur effect
 ///
 /// ```
 ///# use bevy::prelude::*;
@@ -34,33 +34,18 @@ use crate::BoxBlurSettings;
 ///}
 /// ```
 pub struct BoxBlurLens {
-    /// Box blur settings at the end of the tweening
+    /// Box blur settings at the start of the tweening
     start: BoxBlurSettings,
     /// Box blur settings at the end of the tweening
     end: BoxBlurSettings,
 }
 impl crate::BlurSettingLens<BoxBlurSettings> for BoxBlurLens {
     fn new(start: BoxBlurSettings, end: BoxBlurSettings) -> Self {
-        BoxBlurLens {
-            start: start.create_concrete(),
-            end: end.create_concrete(),
-        }
+        BoxBlurLens { start, end }
     }
 }
 impl Lens<BoxBlurSettings> for BoxBlurLens {
     fn lerp(&mut self, target: &mut BoxBlurSettings, ratio: f32) {
-        target.passes = (self.start.passes as f32
-            + (self.end.passes as f32 - self.start.passes as f32) * ratio)
-            .round() as u32;
-        target.kernel_size = {
-            let v1 = self.start.kernel_size;
-            let v2 = self.end.kernel_size;
-            let v = (v1 as f32 + (v2 as f32 - v1 as f32) * ratio).round() as u32;
-            if v % 2 == 0 {
-                v + 1
-            } else {
-                v
-            }
-        };
+        *target = self.start.interpolate(&self.end, ratio);
     }
 }
@@ -0,0 +1,25 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{TextureFormat, TextureUsages},
+        renderer::{RenderAdapter, RenderDevice},
+    },
+};
+
+/// Format of the storage textures written by the compute shaders.
+pub(crate) const COMPUTE_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Whether the GPU supports compute shaders with `workgroup_size` invocations
+/// writing to storage textures of [`COMPUTE_TEXTURE_FORMAT`].
+pub(crate) fn is_compute_supported(world: &World, workgroup_size: u32) -> bool {
+    let render_adapter = world.resource::<RenderAdapter>();
+    let limits = world.resource::<RenderDevice>().limits();
+    // Compute shaders are not supported when those limits are 0, as on WebGL2.
+    limits.max_storage_textures_per_shader_stage > 0
+        && limits.max_compute_invocations_per_workgroup >= workgroup_size
+        && limits.max_compute_workgroup_size_x >= workgroup_size
+        && render_adapter
+            .get_texture_format_features(COMPUTE_TEXTURE_FORMAT)
+            .allowed_usages
+            .contains(TextureUsages::STORAGE_BINDING)
+}
@@ -1,50 +1,94 @@
 // This shader computes the one pass of the kawase blur effect
 
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_camera_blur::edge_mode::{edge_coverage, edge_normalize, edge_sample, viewport_texel_size, viewport_uv}
+#import bevy_camera_blur::alpha_mode::{alpha_finish, alpha_premultiply}
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
-struct KawaseBlurUniforms {
-    /// sampling distance for this pass of the Kawase filter
-    sampling_distance: f32,
+struct DualBlurUniforms {
+    // Distance of the samples, in half pixels of the sampled texture
+    offset: f32,
+    // Value of the `BlurEdgeMode`
+    edge_mode: u32,
+    // Value of the `BlurAlphaMode`
+    alpha_mode: u32,
     // WebGL2 structs must be 16 byte aligned.
-    _webgl2_padding: vec3<f32>,
+    _webgl2_padding: f32,
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    viewport: vec4<f32>,
 }
-@group(0) @binding(2) var<uniform> settings: KawaseBlurUniforms;
+@group(0) @binding(2) var<uniform> settings: DualBlurUniforms;
 
-fn downsample(uv: vec2<f32>, halfpixel: vec2<f32>) -> vec4<f32> {
-    var sum = textureSample(screen_texture, texture_sampler, uv) * 4.0;
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x + halfpixel.x, uv.y + halfpixel.y));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x + halfpixel.x, uv.y - halfpixel.y));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x - halfpixel.x, uv.y - halfpixel.y));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x - halfpixel.x, uv.y + halfpixel.y));
-    return sum / 8.0;
+// Rectangle of the textures of the levels, which have the size of the viewport.
+const LEVEL_VIEWPORT: vec4<f32> = vec4(0.0, 0.0, 1.0, 1.0);
+
+// Accumulates the sample at 'uv' of the 'viewport' with 'weight' in 'color' and 'coverage',
+// see `edge_mode` and `alpha_mode`.
+fn accumulate(
+    color: ptr<function, vec4<f32>>,
+    coverage: ptr<function, f32>,
+    uv: vec2<f32>,
+    viewport: vec4<f32>,
+    weight: f32,
+) {
+    let sample = edge_sample(screen_texture, texture_sampler, uv, viewport, settings.edge_mode);
+    *color += weight * alpha_premultiply(sample, settings.alpha_mode);
+    *coverage += weight * edge_coverage(uv, settings.edge_mode);
+}
+
+fn downsample(uv: vec2<f32>, viewport: vec4<f32>) -> vec4<f32> {
+    let halfpixel = viewport_texel_size(screen_texture, viewport) * 0.5 * settings.offset;
+    var color = vec4(0.0);
+    var coverage = 0.0;
+    accumulate(&color, &coverage, uv, viewport, 4.0 / 8.0);
+    accumulate(&color, &coverage, vec2(uv.x + halfpixel.x, uv.y + halfpixel.y), viewport, 1.0 / 8.0);
+    accumulate(&color, &coverage, vec2(uv.x + halfpixel.x, uv.y - halfpixel.y), viewport, 1.0 / 8.0);
+    accumulate(&color, &coverage, vec2(uv.x - halfpixel.x, uv.y - halfpixel.y), viewport, 1.0 / 8.0);
+    accumulate(&color, &coverage, vec2(uv.x - halfpixel.x, uv.y + halfpixel.y), viewport, 1.0 / 8.0);
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    // The original pixel is approximated by the sampled level, at a different resolution.
+    let texture_uv = viewport_uv(screen_texture, uv, viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
+}
+
+// Downsamples the viewport of the view target to the first level.
+@fragment
+fn fragment_downsample_view(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return downsample(in.uv, settings.viewport);
 }
 
 @fragment
 fn fragment_downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    let halfpixel = texel_size * 0.5;
-    return downsample(in.uv, halfpixel);
+    return downsample(in.uv, LEVEL_VIEWPORT);
 }
 
-fn upsample(uv: vec2<f32>, halfpixel: vec2<f32>) -> vec4<f32> {
-    var sum = textureSample(screen_texture, texture_sampler, vec2(uv.x - halfpixel.x * 2.0, uv.y));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x + halfpixel.x * 2.0, uv.y));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x, uv.y + halfpixel.y * 2.0));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x, uv.y - halfpixel.y * 2.0));
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x - halfpixel.x, uv.y + halfpixel.y)) * 2.0;
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x + halfpixel.x, uv.y + halfpixel.y)) * 2.0;
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x + halfpixel.x, uv.y - halfpixel.y)) * 2.0;
-    sum += textureSample(screen_texture, texture_sampler, vec2(uv.x - halfpixel.x, uv.y - halfpixel.y)) * 2.0;
-    return sum / 12.0;
+fn upsample(uv: vec2<f32>, viewport: vec4<f32>) -> vec4<f32> {
+    let halfpixel = viewport_texel_size(screen_texture, viewport) * 0.5 * settings.offset;
+    var color = vec4(0.0);
+    var coverage = 0.0;
+    accumulate(&color, &coverage, vec2(uv.x - halfpixel.x * 2.0, uv.y), viewport, 1.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x + halfpixel.x * 2.0, uv.y), viewport, 1.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x, uv.y + halfpixel.y * 2.0), viewport, 1.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x, uv.y - halfpixel.y * 2.0), viewport, 1.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x - halfpixel.x, uv.y + halfpixel.y), viewport, 2.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x + halfpixel.x, uv.y + halfpixel.y), viewport, 2.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x + halfpixel.x, uv.y - halfpixel.y), viewport, 2.0 / 12.0);
+    accumulate(&color, &coverage, vec2(uv.x - halfpixel.x, uv.y - halfpixel.y), viewport, 2.0 / 12.0);
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    // The original pixel is approximated by the sampled level, at a different resolution.
+    let texture_uv = viewport_uv(screen_texture, uv, viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
 }
 
+// The last pass writes to the viewport of the view target, the render pass being restricted to it.
 @fragment
 fn fragment_upsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    let halfpixel = texel_size * 0.5;
-    return upsample(in.uv, halfpixel);
-}
\ No newline at end of file
+    return upsample(in.uv, LEVEL_VIEWPORT);
+}
+
+// Copies the viewport of the view target, the render pass being restricted to it.
+@fragment
+fn fragment_copy(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(screen_texture, texture_sampler, viewport_uv(screen_texture, in.uv, settings.viewport));
+}
@@ -1,7 +1,11 @@
 mod pipeline;
 mod settings;
+mod tweening;
 
 pub use settings::DualBlurSettings;
+use settings::DualBlurUniforms;
+#[cfg(feature = "bevy_tweening")]
+pub use tweening::*;
 
 use bevy::{
     asset::load_internal_asset,
@@ -12,11 +16,11 @@ use bevy::{
     prelude::*,
     render::{
         camera::ExtractedCamera,
-        extract_component::ExtractComponentPlugin,
+        extract_component::UniformComponentPlugin,
         render_graph::{RenderGraphApp, ViewNodeRunner},
         render_resource::{
             Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-            TextureView, TextureViewDescriptor,
+            TextureView,
         },
         renderer::RenderDevice,
         texture::{BevyDefault, CachedTexture, TextureCache},
@@ -25,6 +29,12 @@ use bevy::{
 };
 use pipeline::*;
 
+use crate::{
+    alpha_mode::load_alpha_mode_shader, bind_groups::ViewBindGroups,
+    edge_mode::load_edge_mode_shader, extract::ExtractBlurSettingsPlugin,
+    transition::animate_blur_transitions, BlurTransitionFinished,
+};
+
 const DUAL_BLUR_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(0x44c57a955745419aadd439a609c5c191);
 
@@ -59,17 +69,28 @@ impl Plugin for DualBlurPlugin {
             Shader::from_wgsl
         );
         app.register_type::<DualBlurSettings>();
+        load_edge_mode_shader(app);
+        load_alpha_mode_shader(app);
+        app.add_event::<BlurTransitionFinished>()
+            .add_systems(Update, animate_blur_transitions::<DualBlurSettings>);
 
-        app.add_plugins((ExtractComponentPlugin::<DualBlurSettings>::default(),));
+        app.add_plugins((
+            ExtractBlurSettingsPlugin::<DualBlurSettings>::default(),
+            UniformComponentPlugin::<DualBlurUniforms>::default(),
+        ));
 
         let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
         render_app
+            .init_resource::<ViewBindGroups<DualBlurPipeline>>()
             .add_systems(
                 Render,
-                (prepare_dual_blur_textures.in_set(RenderSet::PrepareResources),),
+                (
+                    prepare_dual_blur_textures.in_set(RenderSet::PrepareResources),
+                    prepare_dual_blur_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
             )
             // Add dual blur to the 3d render graph;
             .add_render_graph_node::<ViewNodeRunner<DualBlurNode>>(Core3d, DualBlurLabel)
@@ -109,18 +130,14 @@ fn prepare_dual_blur_textures(
     views: Query<(Entity, &ExtractedCamera, &DualBlurSettings)>,
 ) {
     for (entity, camera, settings) in &views {
-        if let Some(UVec2 {
-            x: width,
-            y: height,
-        }) = camera.physical_viewport_size
-        {
+        if let Some(viewport_size) = camera.physical_viewport_size {
             let mut textures = vec![];
-            for i in 0..settings.downsampling_passes {
+            for size in settings.level_sizes(viewport_size) {
                 let texture_descriptor = TextureDescriptor {
                     label: Some("dual_blur_texture"),
                     size: Extent3d {
-                        width: (width >> i).max(1),
-                        height: (height >> i).max(1),
+                        width: size.x,
+                        height: size.y,
                         depth_or_array_layers: 1,
                     },
                     mip_level_count: 1,
@@ -144,14 +161,8 @@ pub(crate) struct DualBlurTexture {
 }
 
 impl DualBlurTexture {
-    fn view(&self, index: usize) -> TextureView {
-        self.textures[index]
-            .texture
-            .create_view(&TextureViewDescriptor {
-                base_mip_level: 0,
-                mip_level_count: Some(1u32),
-                ..Default::default()
-            })
+    fn view(&self, index: usize) -> &TextureView {
+        &self.textures[index].default_view
     }
     fn len(&self) -> usize {
         self.textures.len()
@@ -1,16 +1,22 @@
-use super::{DualBlurTexture, DUAL_BLUR_SHADER_HANDLE};
+use super::{DualBlurSettings, DualBlurTexture, DualBlurUniforms, DUAL_BLUR_SHADER_HANDLE};
+use crate::bind_groups::ViewBindGroups;
 use bevy::{
     core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
     ecs::query::QueryItem,
     prelude::*,
     render::{
+        camera::ExtractedCamera,
+        extract_component::{ComponentUniforms, DynamicUniformIndex},
         render_graph::{NodeRunError, RenderGraphContext, RenderLabel, ViewNode},
         render_resource::{
-            binding_types::{sampler, texture_2d},
-            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            FilterMode, FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
-            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
-            SamplerBindingType, SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BindingResource,
+            BlendComponent, BlendFactor, BlendOperation, BlendState, CachedRenderPipelineId,
+            ColorTargetState, ColorWrites, FilterMode, FragmentState, LoadOp, MultisampleState,
+            Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
+            RenderPassDescriptor, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+            SamplerDescriptor, ShaderStages, StoreOp, TextureFormat, TextureSampleType,
+            TextureView,
         },
         renderer::{RenderContext, RenderDevice},
         texture::BevyDefault,
@@ -27,23 +33,48 @@ pub(crate) struct DualBlurNode;
 
 // The ViewNode trait is required by the ViewNodeRunner
 impl ViewNode for DualBlurNode {
-    type ViewQuery = (&'static ViewTarget, &'static DualBlurTexture);
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ExtractedCamera,
+        &'static DualBlurTexture,
+        &'static DualBlurSettings,
+        &'static DynamicUniformIndex<DualBlurUniforms>,
+    );
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, dual_blur_texture): QueryItem<Self::ViewQuery>,
+        (view_target, camera, dual_blur_texture, dual_blur_settings, uniform_index): QueryItem<
+            Self::ViewQuery,
+        >,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let dual_blur_pipeline = world.resource::<DualBlurPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
+        let bind_groups = world.resource::<ViewBindGroups<DualBlurPipeline>>();
 
-        let (Some(downsample_pipeline), Some(upsample_pipeline)) = (
+        let (
+            Some(downsample_view_pipeline),
+            Some(downsample_pipeline),
+            Some(upsample_pipeline),
+            Some(upsample_blend_pipeline),
+            Some(copy_pipeline),
+            Some(bind_groups),
+        ) = (
+            pipeline_cache.get_render_pipeline(dual_blur_pipeline.downsample_view_pipeline_id),
             pipeline_cache.get_render_pipeline(dual_blur_pipeline.downsample_pipeline_id),
             pipeline_cache.get_render_pipeline(dual_blur_pipeline.upsample_pipeline_id),
-        ) else {
+            pipeline_cache.get_render_pipeline(dual_blur_pipeline.upsample_blend_pipeline_id),
+            pipeline_cache.get_render_pipeline(dual_blur_pipeline.copy_pipeline_id),
+            bind_groups.get(graph.view_entity()),
+        )
+        else {
             return Ok(());
         };
+        // With a fractional strength, the last upsampling pass is blended
+        // with the downsampled content of its destination.
+        let last_pass_weight = dual_blur_settings.last_pass_weight();
+        let blended = last_pass_weight < 1.0;
 
         render_context
             .command_encoder()
@@ -55,24 +86,16 @@ impl ViewNode for DualBlurNode {
                 .map(|i| dual_blur_texture.view(i))
                 .collect::<Vec<_>>();
 
+            // Each downsampling pass halves the resolution, starting from the viewport of the view target,
+            // and the upsampling passes go back up to it.
             for i in 0..dual_blur_texture.len() {
                 let source_view = if i == 0 {
                     post_process.source
                 } else {
-                    &texture_views[i - 1]
+                    texture_views[i - 1]
                 };
-                let destination_view = &texture_views[i];
-                let bind_group = render_context.render_device().create_bind_group(
-                    "dual_blur_bind_group",
-                    &dual_blur_pipeline.layout,
-                    // It's important for this to match the BindGroupLayout defined in the DualBlurPipeline
-                    &BindGroupEntries::sequential((
-                        // Make sure to use the source view
-                        source_view,
-                        // Use the sampler created for the pipeline
-                        &dual_blur_pipeline.sampler,
-                    )),
-                );
+                let destination_view = texture_views[i];
+                let bind_group = bind_groups.get(source_view);
 
                 let mut render_pass =
                     render_context.begin_tracked_render_pass(RenderPassDescriptor {
@@ -89,28 +112,49 @@ impl ViewNode for DualBlurNode {
                         occlusion_query_set: None,
                     });
 
-                render_pass.set_render_pipeline(downsample_pipeline);
-                render_pass.set_bind_group(0, &bind_group, &[]);
+                // The first pass only samples the viewport of the view target.
+                render_pass.set_render_pipeline(if i == 0 {
+                    downsample_view_pipeline
+                } else {
+                    downsample_pipeline
+                });
+                render_pass.set_bind_group(0, bind_group, &[uniform_index.index()]);
+                render_pass.draw(0..3, 0..1);
+            }
+            // With a single blended pass, the destination has no downsampled content,
+            // the source is copied to it instead.
+            if blended && dual_blur_texture.len() == 1 {
+                let bind_group = bind_groups.get(post_process.source);
+
+                let mut render_pass =
+                    render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                        label: Some("dual_blur_copy_pass"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: post_process.destination,
+                            resolve_target: None,
+                            ops: Operations::default(),
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+                if let Some(viewport) = &camera.viewport {
+                    render_pass.set_camera_viewport(viewport);
+                }
+                render_pass.set_render_pipeline(copy_pipeline);
+                render_pass.set_bind_group(0, bind_group, &[uniform_index.index()]);
                 render_pass.draw(0..3, 0..1);
             }
             for i in (0..dual_blur_texture.len()).rev() {
-                let source_view = &texture_views[i];
+                let blend = blended && i + 1 == dual_blur_texture.len();
+                let source_view = texture_views[i];
                 let destination_view = if i == 0 {
                     post_process.destination
                 } else {
-                    &texture_views[i - 1]
+                    texture_views[i - 1]
                 };
-                let bind_group = render_context.render_device().create_bind_group(
-                    "dual_blur_bind_group",
-                    &dual_blur_pipeline.layout,
-                    // It's important for this to match the BindGroupLayout defined in the DualBlurPipeline
-                    &BindGroupEntries::sequential((
-                        // Make sure to use the source view
-                        source_view,
-                        // Use the sampler created for the pipeline
-                        &dual_blur_pipeline.sampler,
-                    )),
-                );
+                let bind_group = bind_groups.get(source_view);
 
                 let mut render_pass =
                     render_context.begin_tracked_render_pass(RenderPassDescriptor {
@@ -120,15 +164,37 @@ impl ViewNode for DualBlurNode {
                             // to make sure we write to the appropriate texture.
                             view: destination_view,
                             resolve_target: None,
-                            ops: Operations::default(),
+                            // A blended pass keeps the downsampled content of the destination.
+                            ops: if blend {
+                                Operations {
+                                    load: LoadOp::Load,
+                                    store: StoreOp::Store,
+                                }
+                            } else {
+                                Operations::default()
+                            },
                         })],
                         depth_stencil_attachment: None,
                         timestamp_writes: None,
                         occlusion_query_set: None,
                     });
 
-                render_pass.set_render_pipeline(upsample_pipeline);
-                render_pass.set_bind_group(0, &bind_group, &[]);
+                // The other cameras sharing the render target keep their viewports.
+                if let (0, Some(viewport)) = (i, &camera.viewport) {
+                    render_pass.set_camera_viewport(viewport);
+                }
+                if blend {
+                    render_pass.set_render_pipeline(upsample_blend_pipeline);
+                    render_pass.set_blend_constant(Color::rgba(
+                        last_pass_weight,
+                        last_pass_weight,
+                        last_pass_weight,
+                        last_pass_weight,
+                    ));
+                } else {
+                    render_pass.set_render_pipeline(upsample_pipeline);
+                }
+                render_pass.set_bind_group(0, bind_group, &[uniform_index.index()]);
                 render_pass.draw(0..3, 0..1);
             }
         }
@@ -142,8 +208,11 @@ impl ViewNode for DualBlurNode {
 pub struct DualBlurPipeline {
     layout: BindGroupLayout,
     sampler: Sampler,
+    downsample_view_pipeline_id: CachedRenderPipelineId,
     downsample_pipeline_id: CachedRenderPipelineId,
     upsample_pipeline_id: CachedRenderPipelineId,
+    upsample_blend_pipeline_id: CachedRenderPipelineId,
+    copy_pipeline_id: CachedRenderPipelineId,
 }
 
 impl FromWorld for DualBlurPipeline {
@@ -157,6 +226,7 @@ impl FromWorld for DualBlurPipeline {
                 (
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<DualBlurUniforms>(true),
                 ),
             ),
         );
@@ -190,6 +260,25 @@ impl FromWorld for DualBlurPipeline {
                     push_constant_ranges: vec![],
                 });
 
+        // Same as the downsample pipeline, but sampling the viewport of the view target, for the first pass.
+        let downsample_view_pipeline_id = world
+            .resource_mut::<PipelineCache>()
+            .queue_render_pipeline(RenderPipelineDescriptor {
+                label: Some("dual_blur_pipeline".into()),
+                layout: vec![layout.clone()],
+                vertex: fullscreen_shader_vertex_state(),
+                fragment: Some(FragmentState {
+                    shader: DUAL_BLUR_SHADER_HANDLE,
+                    shader_defs: vec![],
+                    entry_point: "fragment_downsample_view".into(),
+                    targets: vec![Some(TextureFormat::bevy_default().into())],
+                }),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                push_constant_ranges: vec![],
+            });
+
         let upsample_pipeline_id =
             world
                 .resource_mut::<PipelineCache>()
@@ -212,11 +301,117 @@ impl FromWorld for DualBlurPipeline {
                     push_constant_ranges: vec![],
                 });
 
+        // Same as the upsample pipeline, but blending its output with the destination content,
+        // using the blend constant as weight.
+        let blend_component = BlendComponent {
+            src_factor: BlendFactor::Constant,
+            dst_factor: BlendFactor::OneMinusConstant,
+            operation: BlendOperation::Add,
+        };
+        let upsample_blend_pipeline_id = world
+            .resource_mut::<PipelineCache>()
+            .queue_render_pipeline(RenderPipelineDescriptor {
+                label: Some("dual_blur_pipeline".into()),
+                layout: vec![layout.clone()],
+                vertex: fullscreen_shader_vertex_state(),
+                fragment: Some(FragmentState {
+                    shader: DUAL_BLUR_SHADER_HANDLE,
+                    shader_defs: vec![],
+                    entry_point: "fragment_upsample".into(),
+                    targets: vec![Some(ColorTargetState {
+                        format: TextureFormat::bevy_default(),
+                        blend: Some(BlendState {
+                            color: blend_component,
+                            alpha: blend_component,
+                        }),
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                push_constant_ranges: vec![],
+            });
+
+        let copy_pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("dual_blur_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: DUAL_BLUR_SHADER_HANDLE,
+                        shader_defs: vec![],
+                        entry_point: "fragment_copy".into(),
+                        targets: vec![Some(TextureFormat::bevy_default().into())],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                });
+
         Self {
             layout,
             sampler,
+            downsample_view_pipeline_id,
             downsample_pipeline_id,
             upsample_pipeline_id,
+            upsample_blend_pipeline_id,
+            copy_pipeline_id,
         }
     }
 }
+
+impl DualBlurPipeline {
+    // Creates the bind group of a pass sampling `source`.
+    fn create_bind_group(
+        &self,
+        render_device: &RenderDevice,
+        source: &TextureView,
+        settings_binding: BindingResource,
+    ) -> BindGroup {
+        render_device.create_bind_group(
+            "dual_blur_bind_group",
+            &self.layout,
+            // It's important for this to match the BindGroupLayout defined in the DualBlurPipeline
+            &BindGroupEntries::sequential((
+                // Make sure to use the source view
+                source,
+                // Use the sampler created for the pipeline
+                &self.sampler,
+                // Set the settings binding
+                settings_binding,
+            )),
+        )
+    }
+}
+
+// The first downsampling pass samples one of the main textures of the view,
+// and the following passes the textures of each level, a bind group is prepared for each of them.
+pub(crate) fn prepare_dual_blur_bind_groups(
+    render_device: Res<RenderDevice>,
+    dual_blur_pipeline: Res<DualBlurPipeline>,
+    settings_uniforms: Res<ComponentUniforms<DualBlurUniforms>>,
+    mut bind_groups: ResMut<ViewBindGroups<DualBlurPipeline>>,
+    views: Query<(Entity, &ViewTarget, &DualBlurTexture)>,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) = (
+        settings_uniforms.uniforms().buffer(),
+        settings_uniforms.uniforms().binding(),
+    ) else {
+        return;
+    };
+    for (view, view_target, dual_blur_texture) in &views {
+        let mut sources = vec![
+            view_target.main_texture_view(),
+            view_target.main_texture_other_view(),
+        ];
+        sources.extend((0..dual_blur_texture.len()).map(|i| dual_blur_texture.view(i)));
+        bind_groups.update(view, &sources, Some(buffer.id()), |source| {
+            dual_blur_pipeline.create_bind_group(&render_device, source, settings_binding.clone())
+        });
+    }
+}
@@ -1,62 +1,340 @@
-use bevy::ecs::query::QueryItem;
 use bevy::prelude::*;
-use bevy::render::extract_component::ExtractComponent;
+use bevy::render::render_resource::ShaderType;
+
+use crate::{
+    extract::ExtractBlurSettings, viewport::viewport_uv_rect, BlurAlphaMode, BlurEdgeMode,
+    BlurQuality, BlurSetting,
+};
 
 /// Applies a dual blur effect to a 2d or 3d camera.
 ///
-#[derive(Component, Reflect, Clone, Debug)]
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
 #[reflect(Component, Default)]
 pub struct DualBlurSettings {
-    /// Defines the number of downsampling passes to do. There will be an equivalent number of upsampling passes.
+    /// Defines the strength of the blur, as the number of downsampling passes to do.
+    /// There will be an equivalent number of upsampling passes.
     /// Each of these passes will use the kernel described by Marius Bjorge in his presentation.
+    ///
+    /// When fractional, the number of passes is rounded up, and the last upsampling pass is blended
+    /// with the downsampled content of its destination, the fractional part being the weight of the last pass.
+    /// - It will be clamped to the range [0.0..8.0]
+    /// - Defaults to 4.0
+    pub strength: f32,
+    /// Distance of the samples of each pass from the sampled pixel, in half pixels of the sampled texture,
+    /// as the offset of the presentation of Marius Bjorge.
+    /// This widens the blur of each pass without impacting the computational cost, but sacrificing quality.
     /// - It will be clamped to the range [0..8]
-    /// - Defaults to 4
-    pub downsampling_passes: u32,
+    /// - Non integer values are supported, this will take advantage of linear filtering.
+    /// - Defaults to 1, the half pixel offset of the original algorithm.
+    pub offset: f32,
+    /// Ratio between the resolution of a downsampling level and the resolution of the next one.
+    /// Lower values give finer steps of blur radius between strengths, but a higher computational cost.
+    /// - It will be clamped to the range [1.25..4]
+    /// - Defaults to 2, each level halving the resolution.
+    pub scale_factor: f32,
+    /// Defines how the pixels beyond the edges of the view are sampled.
+    /// - Defaults to [`BlurEdgeMode::Clamp`]
+    pub edge_mode: BlurEdgeMode,
+    /// Defines how the alpha channel is blurred, for cameras rendering to a transparent target.
+    /// - Defaults to [`BlurAlphaMode::Straight`]
+    pub alpha_mode: BlurAlphaMode,
 }
 impl Default for DualBlurSettings {
     fn default() -> Self {
         Self {
-            downsampling_passes: 4,
+            strength: 4.0,
+            offset: 1.0,
+            scale_factor: 2.0,
+            edge_mode: BlurEdgeMode::Clamp,
+            alpha_mode: BlurAlphaMode::Straight,
         }
     }
 }
 impl crate::BlurSetting for DualBlurSettings {
     const NO_BLUR: DualBlurSettings = DualBlurSettings {
-        downsampling_passes: 0,
+        strength: 0.0,
+        offset: 1.0,
+        scale_factor: 2.0,
+        edge_mode: BlurEdgeMode::Clamp,
+        alpha_mode: BlurAlphaMode::Straight,
     };
 
     fn sampling_per_pixel(&self) -> f32 {
-        // For each pass there is 5 for downsampling but at image size/4 + 8 for upsampling.
-        (0..self.downsampling_passes).fold(0.0, |samplings, pass| {
-            samplings + (5.0 / 4.0 + 8.0) / (4.0_f32.powi(pass as i32))
+        // For each pass there is 5 for downsampling at the size of the level, `scale_factor²` times smaller
+        // than the upsampled size, + 8 for upsampling back to the previous level, or to the view target for the first pass.
+        // The offset does not change the number of samplings.
+        let settings = self.create_concrete();
+        let area_factor = settings.scale_factor.powi(2);
+        (0..settings.downsampling_passes()).fold(0.0, |samplings, pass| {
+            samplings + (5.0 / area_factor + 8.0) / area_factor.powi(pass as i32)
         })
     }
 
     fn passes(&self) -> u32 {
-        self.downsampling_passes * 2
+        self.downsampling_passes() * 2
+    }
+
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64 {
+        let pixels = |size: UVec2| size.x as u64 * size.y as u64;
+        let settings = self.create_concrete();
+        let level_sizes = settings.level_sizes(viewport_size);
+        // A blended single pass first copies the source to the destination with 1 sampling.
+        let copy = if settings.downsampling_passes() == 1 && settings.last_pass_weight() < 1.0 {
+            pixels(viewport_size)
+        } else {
+            0
+        };
+        // Downsampling passes write to each level with 5 samplings,
+        // upsampling passes write back to the previous level with 8 samplings.
+        copy + level_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| {
+                let upsampled_size = if i == 0 {
+                    viewport_size
+                } else {
+                    level_sizes[i - 1]
+                };
+                5 * pixels(size) + 8 * pixels(upsampled_size)
+            })
+            .sum::<u64>()
+    }
+
+    fn equivalent_sigma(&self) -> f32 {
+        // The blended last pass gives a mix of two kernels with the same mean,
+        // whose variance is the weighted mean of the two variances.
+        let settings = self.create_concrete();
+        let passes = settings.downsampling_passes();
+        (0..passes)
+            .map(|pass| {
+                let weight = if pass + 1 == passes {
+                    settings.last_pass_weight()
+                } else {
+                    1.0
+                };
+                weight * settings.pass_variance(pass)
+            })
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        DualBlurSettings {
+            strength: self.strength + (other.strength - self.strength) * t,
+            offset: self.offset + (other.offset - self.offset) * t,
+            scale_factor: self.scale_factor + (other.scale_factor - self.scale_factor) * t,
+            edge_mode: if t < 0.5 {
+                self.edge_mode
+            } else {
+                other.edge_mode
+            },
+            alpha_mode: if t < 0.5 {
+                self.alpha_mode
+            } else {
+                other.alpha_mode
+            },
+        }
+        .create_concrete()
     }
 }
 impl DualBlurSettings {
+    /// Computes the `DualBlurSettings` whose blur is the closest to a gaussian blur of the given `sigma`.
+    ///
+    /// The default `offset` and `scale_factor` are used, the fractional part of the strength
+    /// being used to match the variance of the gaussian blur, up to the maximum strength of 8.0.
+    pub fn from_sigma(sigma: f32) -> DualBlurSettings {
+        let settings = DualBlurSettings::default();
+        DualBlurSettings {
+            strength: settings.strength_for_sigma(sigma),
+            ..settings
+        }
+    }
+    // Strength whose blur is the closest to a gaussian blur of the given `sigma`, with the `offset` and `scale_factor` of `self`.
+    fn strength_for_sigma(&self, sigma: f32) -> f32 {
+        let mut remaining_variance = sigma.max(0.0).powi(2);
+        let mut strength = 0.0;
+        for pass in 0..8 {
+            let variance = self.pass_variance(pass);
+            if remaining_variance <= variance {
+                strength += remaining_variance / variance;
+                break;
+            }
+            remaining_variance -= variance;
+            strength += 1.0;
+        }
+        strength
+    }
+    /// Computes the settings used for a [`BlurQuality`] by the default [`BlurQualityMapping`](crate::BlurQualityMapping),
+    /// with about the same equivalent sigma.
+    ///
+    /// - [`BlurQuality::Low`] and [`BlurQuality::Medium`] raise the `scale_factor` to at least 3 and 2.5,
+    ///   for fewer and smaller downsampling levels.
+    /// - [`BlurQuality::High`] keeps the settings.
+    /// - [`BlurQuality::Ultra`] lowers the `scale_factor` to at most 1.5, for more and bigger downsampling levels.
+    ///
+    /// The strength is the one of the same equivalent sigma with the new `scale_factor`.
+    pub fn with_quality(&self, quality: BlurQuality) -> DualBlurSettings {
+        let settings = self.create_concrete();
+        let scale_factor = match quality {
+            BlurQuality::Low => settings.scale_factor.max(3.0),
+            BlurQuality::Medium => settings.scale_factor.max(2.5),
+            BlurQuality::High => return self.clone(),
+            BlurQuality::Ultra => settings.scale_factor.min(1.5),
+        };
+        let mapped = DualBlurSettings {
+            scale_factor,
+            ..settings.clone()
+        };
+        DualBlurSettings {
+            strength: mapped.strength_for_sigma(settings.equivalent_sigma()),
+            ..mapped
+        }
+    }
+    /// Number of downsampling passes to do, being the strength rounded up.
+    pub(crate) fn downsampling_passes(&self) -> u32 {
+        self.strength.ceil() as u32
+    }
+    /// Weight of the last upsampling pass, when blended with the downsampled content of its destination.
+    pub(crate) fn last_pass_weight(&self) -> f32 {
+        self.strength - (self.downsampling_passes().max(1) - 1) as f32
+    }
+    /// Computes the size of the texture of each downsampling level, for a viewport of `viewport_size`.
+    ///
+    /// The first level has the resolution of the viewport divided by `scale_factor`,
+    /// each following level dividing it again.
+    pub(crate) fn level_sizes(&self, viewport_size: UVec2) -> Vec<UVec2> {
+        (0..self.downsampling_passes())
+            .map(|i| {
+                (viewport_size.as_vec2() / self.scale_factor.powi(i as i32 + 1))
+                    .as_uvec2()
+                    .max(UVec2::ONE)
+            })
+            .collect()
+    }
     /// Computes a new `DualBlurSettings` where each attribute is legal as expected by the shader.
     pub fn create_concrete(&self) -> DualBlurSettings {
-        let downsampling_passes = self.downsampling_passes.clamp(0, 8);
         DualBlurSettings {
-            downsampling_passes,
+            strength: self.strength.clamp(0.0, 8.0),
+            offset: self.offset.clamp(0.0, 8.0),
+            scale_factor: self.scale_factor.clamp(1.25, 4.0),
+            edge_mode: self.edge_mode,
+            alpha_mode: self.alpha_mode,
         }
     }
+    // Variance, in pixels, of the downsampling/upsampling pair of the given `pass`.
+    // Each pair divides the resolution by `scale_factor`, multiplying the variance of the previous one by `scale_factor²`.
+    fn pass_variance(&self, pass: u32) -> f32 {
+        dual_pair_variance(self.offset, self.scale_factor) * self.scale_factor.powi(2 * pass as i32)
+    }
+}
+
+// Variance, in pixels, of the first downsampling/upsampling pair.
+// The samples of the downsampling pass add `offset²/8` pixels², and the samples of the upsampling pass
+// `offset²/3` pixels² of the level, `scale_factor` times bigger. The linear filtering adds about 5/32
+// pixels² of the level, measured on the impulse response of the default settings.
+fn dual_pair_variance(offset: f32, scale_factor: f32) -> f32 {
+    let level_variance = scale_factor.powi(2);
+    offset.powi(2) * (1.0 / 8.0 + level_variance / 3.0) + 5.0 / 32.0 * level_variance
 }
 
-impl ExtractComponent for DualBlurSettings {
-    type QueryData = &'static Self;
+impl ExtractBlurSettings for DualBlurSettings {
+    type Out = (DualBlurSettings, DualBlurUniforms);
+
+    // The strength is the one of the scaled equivalent sigma.
+    fn scaled(&self, factor: f32) -> Self {
+        let settings = self.create_concrete();
+        DualBlurSettings {
+            strength: settings.strength_for_sigma(settings.equivalent_sigma() * factor),
+            ..settings
+        }
+    }
+
+    fn map_quality(&self, quality: BlurQuality) -> Self {
+        self.with_quality(quality)
+    }
 
-    type QueryFilter = ();
-    type Out = DualBlurSettings;
+    fn capped(&self, max_sigma: f32) -> Self {
+        crate::extract::capped_to_sigma(self, max_sigma)
+    }
 
-    fn extract_component(settings: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
-        if settings.downsampling_passes == 0 {
+    fn extract(&self, camera: &Camera) -> Option<Self::Out> {
+        if self.strength <= 0.0 {
             None
         } else {
-            Some(settings.create_concrete())
+            let settings = self.create_concrete();
+            let uniforms = DualBlurUniforms {
+                offset: settings.offset,
+                edge_mode: settings.edge_mode.shader_value(),
+                alpha_mode: settings.alpha_mode.shader_value(),
+                _webgl2_padding: 0.0,
+                viewport: viewport_uv_rect(camera.viewport.as_ref(), camera.physical_target_size()),
+            };
+            Some((settings, uniforms))
         }
     }
 }
+
+/// The uniform struct extracted from [`DualBlurSettings`] attached to a Camera.
+/// Will be available for use in the dual blur shader.
+#[derive(Component, ShaderType, Clone)]
+pub struct DualBlurUniforms {
+    // Legalized offset
+    pub offset: f32,
+    // Value of the legalized edge_mode
+    pub edge_mode: u32,
+    // Value of the alpha_mode
+    pub alpha_mode: u32,
+    // webgl2 requires 16B padding
+    pub _webgl2_padding: f32,
+    // UV rectangle of the viewport of the camera in the view target
+    pub viewport: Vec4,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlurSetting;
+
+    #[test]
+    fn levels_halve_the_resolution() {
+        let settings = DualBlurSettings {
+            strength: 4.0,
+            ..default()
+        };
+        assert_eq!(
+            settings.level_sizes(UVec2::new(1920, 1080)),
+            vec![
+                UVec2::new(960, 540),
+                UVec2::new(480, 270),
+                UVec2::new(240, 135),
+                UVec2::new(120, 67),
+            ]
+        );
+        // A fractional strength adds a level, and levels never get empty.
+        let settings = DualBlurSettings {
+            strength: 2.5,
+            ..default()
+        };
+        assert_eq!(
+            settings.level_sizes(UVec2::new(5, 3)),
+            vec![UVec2::new(2, 1), UVec2::new(1, 1), UVec2::new(1, 1)]
+        );
+        // A smaller scale factor gives more levels of similar sizes.
+        let settings = DualBlurSettings {
+            strength: 3.0,
+            scale_factor: 1.5,
+            ..default()
+        };
+        assert_eq!(
+            settings.level_sizes(UVec2::new(1920, 1080)),
+            vec![
+                UVec2::new(1280, 720),
+                UVec2::new(853, 480),
+                UVec2::new(568, 320),
+            ]
+        );
+        assert!(DualBlurSettings::NO_BLUR
+            .level_sizes(UVec2::new(1920, 1080))
+            .is_empty());
+    }
+}
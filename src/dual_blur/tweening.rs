@@ -0,0 +1,51 @@
+#![cfg(feature = "bevy_tweening")]
+use bevy_tweening::Lens;
+
+use crate::{BlurSetting, DualBlurSettings};
+
+/// A `bevy_tweening` Lens implementation to allow animation of the dual blur.
+///
+/// This will tweens the `strength`, `offset` and `scale_factor` attributes
+/// of the [`DualBlurSettings`], see [`BlurSetting::interpolate`].
+/// # Example
+///
+/// System that will create an animation from a no blur to the default blur effect
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_tweening::*;
+///# use bevy_camera_blur::*;
+///# use std::time::Duration;
+///
+///fn blur(
+///    mut commands: Commands,
+///    camera: Query<Entity, (With<Camera>, With<DualBlurSettings>)>,
+///) {
+///    let tween = Tween::new(
+///        EaseFunction::QuadraticInOut,
+///        Duration::from_millis(500),
+///        DualBlurLens::new(
+///             DualBlurSettings::NO_BLUR,
+///             DualBlurSettings::default(),
+///        )
+///    );
+///    let camera_entity = camera.single();
+///    commands.entity(camera_entity).insert(Animator::new(tween));
+///}
+/// ```
+pub struct DualBlurLens {
+    /// Dual blur settings at the start of the tweening
+    start: DualBlurSettings,
+    /// Dual blur settings at the end of the tweening
+    end: DualBlurSettings,
+}
+impl crate::BlurSettingLens<DualBlurSettings> for DualBlurLens {
+    fn new(start: DualBlurSettings, end: DualBlurSettings) -> Self {
+        DualBlurLens { start, end }
+    }
+}
+impl Lens<DualBlurSettings> for DualBlurLens {
+    fn lerp(&mut self, target: &mut DualBlurSettings, ratio: f32) {
+        *target = self.start.interpolate(&self.end, ratio);
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::{asset::load_internal_asset, prelude::*};
+
+const EDGE_MODE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x8d3c1f0e6a2b47c59e14b7a3d20f6c81);
+
+/// Defines how a blur samples the pixels beyond the edges of the view.
+///
+/// The edge mode is applied by the shaders at the edges of the viewport of the camera.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlurEdgeMode {
+    /// The pixels of the edges are repeated.
+    /// A bright pixel on the border therefore streaks inward on large blurs.
+    #[default]
+    Clamp,
+    /// The view is mirrored at its edges, for seamless textures.
+    Mirror,
+    /// The view is repeated beyond its edges, for tiled backgrounds.
+    Wrap,
+    /// The pixels beyond the edges are transparent black.
+    Transparent {
+        /// When `false`, the borders of the view darken and get transparent.
+        ///
+        /// When `true`, the pixels beyond the edges are ignored instead,
+        /// the weights of the other pixels being renormalized so that the borders keep their brightness.
+        renormalize: bool,
+    },
+}
+
+impl BlurEdgeMode {
+    // Value of the `edge_mode` field of the uniforms, as expected by the `bevy_camera_blur::edge_mode` WGSL module.
+    pub(crate) fn shader_value(self) -> u32 {
+        match self {
+            BlurEdgeMode::Clamp => 0,
+            BlurEdgeMode::Mirror => 1,
+            BlurEdgeMode::Wrap => 2,
+            BlurEdgeMode::Transparent { renormalize: false } => 3,
+            BlurEdgeMode::Transparent { renormalize: true } => 4,
+        }
+    }
+}
+
+// Loads the `bevy_camera_blur::edge_mode` WGSL module imported by the blur shaders.
+// Each blur plugin loads it, so that they can be added independently.
+pub(crate) fn load_edge_mode_shader(app: &mut App) {
+    load_internal_asset!(
+        app,
+        EDGE_MODE_SHADER_HANDLE,
+        "edge_mode.wgsl",
+        Shader::from_wgsl
+    );
+    app.register_type::<BlurEdgeMode>();
+}
@@ -0,0 +1,70 @@
+// Functions sampling a texture beyond its edges according to the `BlurEdgeMode` of the settings.
+//
+// A blur accumulates the weighted colors returned by `edge_sample` and the weighted coverages returned by
+// `edge_coverage`, then gives both sums to `edge_normalize` to get its result.
+//
+// The UV coordinates are relative to the viewport of the camera, the 'viewport' rectangle of the sampled texture
+// given as its min in xy and its max in zw: the edges are those of the viewport.
+
+#define_import_path bevy_camera_blur::edge_mode
+
+const EDGE_MODE_CLAMP: u32 = 0u;
+const EDGE_MODE_MIRROR: u32 = 1u;
+const EDGE_MODE_WRAP: u32 = 2u;
+const EDGE_MODE_TRANSPARENT: u32 = 3u;
+const EDGE_MODE_TRANSPARENT_RENORMALIZED: u32 = 4u;
+
+// Whether 'uv' is inside the texture.
+fn is_inside(uv: vec2<f32>) -> bool {
+    return all(uv >= vec2(0.0)) && all(uv <= vec2(1.0));
+}
+
+// Coverage of the sample at 'uv': 0 beyond the edges with the transparent modes, 1 otherwise.
+fn edge_coverage(uv: vec2<f32>, edge_mode: u32) -> f32 {
+    if edge_mode >= EDGE_MODE_TRANSPARENT {
+        return select(0.0, 1.0, is_inside(uv));
+    }
+    return 1.0;
+}
+
+// Size of a texel of 'texture' in the UV coordinates relative to its 'viewport' rectangle.
+fn viewport_texel_size(texture: texture_2d<f32>, viewport: vec4<f32>) -> vec2<f32> {
+    return 1.0 / (vec2<f32>(textureDimensions(texture)) * (viewport.zw - viewport.xy));
+}
+
+// Maps 'uv', relative to the 'viewport' rectangle of 'texture', to the UV coordinates of the texture.
+// It is clamped half a texel inside the viewport, so that linear filtering never blends the pixels beyond it.
+fn viewport_uv(texture: texture_2d<f32>, uv: vec2<f32>, viewport: vec4<f32>) -> vec2<f32> {
+    let half_texel = 0.5 / vec2<f32>(textureDimensions(texture));
+    let texture_uv = mix(viewport.xy, viewport.zw, uv);
+    return clamp(texture_uv, viewport.xy + half_texel, max(viewport.xy + half_texel, viewport.zw - half_texel));
+}
+
+// Samples 'texture' at 'uv', the pixels beyond the edges being given by 'edge_mode'.
+//
+// The texture is sampled at its first level, the wrapped coordinates having no meaningful derivatives.
+fn edge_sample(
+    texture: texture_2d<f32>,
+    texture_sampler: sampler,
+    uv: vec2<f32>,
+    viewport: vec4<f32>,
+    edge_mode: u32,
+) -> vec4<f32> {
+    var edge_uv = uv;
+    if edge_mode == EDGE_MODE_MIRROR {
+        edge_uv = 1.0 - abs(1.0 - fract(uv * 0.5) * 2.0);
+    } else if edge_mode == EDGE_MODE_WRAP {
+        edge_uv = fract(uv);
+    }
+    let texture_uv = viewport_uv(texture, edge_uv, viewport);
+    return edge_coverage(uv, edge_mode) * textureSampleLevel(texture, texture_sampler, texture_uv, 0.0);
+}
+
+// Result of a blur whose normalized weights gave the sums 'color' and 'coverage'.
+// With the renormalized transparent mode, the pixels beyond the edges are ignored.
+fn edge_normalize(color: vec4<f32>, coverage: f32, edge_mode: u32) -> vec4<f32> {
+    if edge_mode == EDGE_MODE_TRANSPARENT_RENORMALIZED && coverage > 0.0 {
+        return color / coverage;
+    }
+    return color;
+}
@@ -0,0 +1,157 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    prelude::*,
+    render::{Extract, ExtractSchedule, RenderApp},
+};
+
+use crate::{
+    quality::init_quality_mapping, BlurAccessibility, BlurQuality, BlurQualityMapping, BlurSetting,
+    BlurUnits,
+};
+
+// Settings of a blur extracted from the Camera entities to their view in the render world.
+pub(crate) trait ExtractBlurSettings: Component + Clone {
+    // Components inserted on the view.
+    type Out: Bundle;
+
+    // Computes the settings whose blur is `factor` times wider.
+    fn scaled(&self, factor: f32) -> Self;
+
+    // Computes the settings used for a quality by the default `BlurQualityMapping`.
+    fn map_quality(&self, quality: BlurQuality) -> Self;
+
+    // Computes the settings whose equivalent sigma is at most `max_sigma`.
+    fn capped(&self, max_sigma: f32) -> Self;
+
+    // Computes the components of the view for settings in physical pixels,
+    // `None` when they do not blur and the post-processing effect is skipped.
+    fn extract(&self, camera: &Camera) -> Option<Self::Out>;
+}
+
+// Extracts the settings `T` of each camera, converted from the `BlurUnits` of the camera to physical pixels,
+// then mapped to the current `BlurQuality`, and capped by the `BlurAccessibility`.
+pub(crate) struct ExtractBlurSettingsPlugin<T>(PhantomData<T>);
+
+impl<T> Default for ExtractBlurSettingsPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ExtractBlurSettings> Plugin for ExtractBlurSettingsPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BlurUnits>()
+            .register_type::<BlurAccessibility>();
+        init_quality_mapping(app, T::map_quality);
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.add_systems(ExtractSchedule, extract_blur_settings::<T>);
+    }
+}
+
+// Data of the cameras needed to convert their settings to physical pixels.
+type CameraQueryData<T> = (
+    Entity,
+    &'static T,
+    &'static Camera,
+    Option<&'static BlurUnits>,
+    Option<&'static OrthographicProjection>,
+    Option<&'static Projection>,
+);
+
+fn extract_blur_settings<T: ExtractBlurSettings>(
+    mut commands: Commands,
+    mut previous_len: Local<usize>,
+    cameras: Extract<Query<CameraQueryData<T>>>,
+    quality: Extract<Option<Res<BlurQuality>>>,
+    quality_mapping: Extract<Option<Res<BlurQualityMapping<T>>>>,
+    accessibility: Extract<Option<Res<BlurAccessibility>>>,
+) {
+    let quality = quality.as_deref().copied().unwrap_or_default();
+    let accessibility = accessibility.as_deref().copied().unwrap_or_default();
+    let max_sigma = accessibility.max_strength;
+    if accessibility.disable_all || max_sigma.is_some_and(|max_sigma| max_sigma <= 0.0) {
+        *previous_len = 0;
+        return;
+    }
+    let mut values = Vec::with_capacity(*previous_len);
+    for (entity, settings, camera, units, orthographic_projection, projection) in &cameras {
+        let factor = units.copied().unwrap_or_default().physical_pixels_per_unit(
+            camera,
+            orthographic_projection,
+            projection,
+        );
+        let settings = if factor == 1.0 {
+            settings.clone()
+        } else {
+            settings.scaled(factor)
+        };
+        let settings = match quality_mapping.as_deref() {
+            Some(quality_mapping) => (quality_mapping.map)(&settings, quality),
+            None => settings,
+        };
+        let settings = match max_sigma {
+            Some(max_sigma) => settings.capped(max_sigma),
+            None => settings,
+        };
+        if let Some(extracted) = settings.extract(camera) {
+            values.push((entity, extracted));
+        }
+    }
+    *previous_len = values.len();
+    commands.insert_or_spawn_batch(values);
+}
+
+// Scales down the settings whose equivalent sigma is above `max_sigma`.
+pub(crate) fn capped_to_sigma<T: ExtractBlurSettings + BlurSetting>(
+    settings: &T,
+    max_sigma: f32,
+) -> T {
+    let sigma = settings.equivalent_sigma();
+    if sigma > max_sigma {
+        settings.scaled(max_sigma / sigma)
+    } else {
+        settings.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxBlurSettings, DualBlurSettings, GaussianBlurSettings, KawaseBlurSettings};
+
+    fn assert_scaled<T: ExtractBlurSettings + BlurSetting>(settings: T, factor: f32) {
+        let expected = settings.equivalent_sigma() * factor;
+        let scaled = settings.scaled(factor).equivalent_sigma();
+        assert!(
+            (scaled / expected - 1.0).abs() < 0.1,
+            "{scaled} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn scaled_settings_keep_their_look() {
+        for factor in [0.5, 2.5, 10.0] {
+            assert_scaled(GaussianBlurSettings::default(), factor);
+            assert_scaled(BoxBlurSettings::default(), factor);
+            // The Kawase blur reaches an equivalent sigma of about 29.6 at most.
+            if factor < 5.0 {
+                assert_scaled(KawaseBlurSettings::default(), factor);
+            }
+            assert_scaled(DualBlurSettings::default(), factor);
+        }
+    }
+
+    #[test]
+    fn capped_settings_are_not_stronger() {
+        let gaussian = GaussianBlurSettings::from_sigma(20.0);
+        assert!((gaussian.capped(8.0).equivalent_sigma() - 8.0).abs() < 0.5);
+        assert_eq!(gaussian.capped(40.0), gaussian);
+        let dual = DualBlurSettings::default();
+        assert!(dual.capped(4.0).equivalent_sigma() <= 4.0 * 1.1);
+    }
+}
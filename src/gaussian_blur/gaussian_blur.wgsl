@@ -3,57 +3,76 @@
 // The effect uses two passes, an horizontal pass and a vertical pass
 
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
-
-const SQ_TWO_PI:f32 = 2.5066282;
-const E:f32 = 2.71828;
-
-// Compute the the gaussian weight of the value 'v' given the 'sigma'.
-fn gaussian_weight(v: i32, sigma: f32) -> f32 {
-    let sigma_square = sigma * sigma;
-    return (1.0 / (SQ_TWO_PI * sigma)) * pow(E, -f32(v * v) / (2.0 * sigma_square));
-}
+#import bevy_camera_blur::edge_mode::{edge_coverage, edge_normalize, edge_sample, viewport_texel_size, viewport_uv}
+#import bevy_camera_blur::alpha_mode::{alpha_finish, alpha_premultiply}
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
 struct GaussianBlurUniforms {
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    // The UV coordinates of the passes are relative to it, the render passes being restricted to the viewport.
+    viewport: vec4<f32>,
     kernel_size: i32,
     sigma: f32,
     sampling_distance_factor: f32,
-    // WebGL2 structs must be 16 byte aligned.
-    _webgl2_padding: f32,
+    tap_count: u32,
+    // Value of the `BlurEdgeMode`
+    edge_mode: u32,
+    // Value of the `BlurAlphaMode`
+    alpha_mode: u32,
+    // Offsets and normalized weights of the taps, computed on the CPU, packed by two.
+    // Each tap but the first merges two adjacent pixels of the kernel, thanks to linear filtering.
+    taps: array<vec4<f32>, 51>,
 }
 @group(0) @binding(2) var<uniform> settings: GaussianBlurUniforms;
 
+// Provides the offset and the weight of the tap 'i'.
+fn tap(i: u32) -> vec2<f32> {
+    let taps = settings.taps[i / 2u];
+    if i % 2u == 0u {
+        return taps.xy;
+    }
+    return taps.zw;
+}
+
+// Samples the tap at 'uv', beyond the edges and premultiplied according to the settings.
+fn blur_sample(uv: vec2<f32>) -> vec4<f32> {
+    let sample = edge_sample(screen_texture, texture_sampler, uv, settings.viewport, settings.edge_mode);
+    return alpha_premultiply(sample, settings.alpha_mode);
+}
+
+// Blurs along 'direction', in texels.
+fn blur(uv: vec2<f32>, direction: vec2<f32>) -> vec4<f32> {
+    let step = direction * settings.sampling_distance_factor * viewport_texel_size(screen_texture, settings.viewport);
+    let center = tap(0u);
+    var color = center.y * blur_sample(uv);
+    var coverage = center.y * edge_coverage(uv, settings.edge_mode);
+    // The pipeline is specialized on the number of taps, so that the loop can be unrolled.
+    // It can be higher than the number of taps of the settings, the additional taps having a null weight.
+    for (var i = 1u; i < #{TAP_COUNT}u; i++) {
+        let tap = tap(i);
+        for (var side = -1.0; side <= 1.0; side += 2.0) {
+            let tap_uv = uv + side * tap.x * step;
+            color += tap.y * blur_sample(tap_uv);
+            coverage += tap.y * edge_coverage(tap_uv, settings.edge_mode);
+        }
+    }
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    let texture_uv = viewport_uv(screen_texture, uv, settings.viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
+}
+
 @fragment
 fn fragment_horizontal(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let upper = (settings.kernel_size - 1) / 2;
-    let lower = -upper;
-    var color = vec4(0.0);
-    var weight_sum = 0.0;
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    for (var x = lower; x <= upper ; x ++) {
-        let uv = in.uv + vec2<f32>(f32(x) * settings.sampling_distance_factor * texel_size.x, 0.);
-        let weight = gaussian_weight(x, settings.sigma);
-        color += weight * textureSample(screen_texture, texture_sampler, uv);
-        weight_sum += weight;
-    }
-    return color / weight_sum;
+    return blur(in.uv, vec2(1.0, 0.0));
 }
 @fragment
 fn fragment_vertical(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let upper = (settings.kernel_size - 1) / 2;
-    let lower = -upper;
-    var color = vec4(0.0);
-    var weight_sum = 0.0;
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
-    for (var y = lower; y <= upper ; y ++) {
-        let uv = in.uv + vec2<f32>(0.0, f32(y) * settings.sampling_distance_factor * texel_size.y);
-        let weight = gaussian_weight(y, settings.sigma);
-        color += weight * textureSample(screen_texture, texture_sampler, uv);
-        weight_sum += weight;
-    }
-    return color / weight_sum;
+    return blur(in.uv, vec2(0.0, 1.0));
 }
 
+// Copies the result of the compute shaders, written in the viewport of a texture of the size of the view target.
+@fragment
+fn fragment_copy(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(screen_texture, texture_sampler, viewport_uv(screen_texture, in.uv, settings.viewport));
+}
@@ -0,0 +1,85 @@
+// This shader computes the gaussian blur effect with compute shaders
+
+// The effect uses two passes, an horizontal pass and a vertical pass.
+// Each workgroup blurs a segment of a line (or a column) of the texture:
+// the pixels of the segment and the kernel extent on each side (the apron) are first loaded
+// in workgroup shared memory, so that each pixel is only read once from the texture.
+// Only the viewport of the camera is blurred, the textures having the size of the view target.
+
+const WORKGROUP_SIZE: u32 = #{WORKGROUP_SIZE}u;
+// Half of the highest kernel size, 401.
+const MAX_RADIUS: u32 = 200u;
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba16float, write>;
+struct GaussianBlurUniforms {
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    viewport: vec4<f32>,
+    kernel_size: i32,
+    sigma: f32,
+    sampling_distance_factor: f32,
+    tap_count: u32,
+    // Always clamping, the compute shaders being only used with `BlurEdgeMode::Clamp`.
+    _edge_mode: u32,
+    // Always straight, the compute shaders being only used with `BlurAlphaMode::Straight`.
+    _alpha_mode: u32,
+    // Taps of the fragment shaders, merging adjacent pixels, which the compute shader does not use.
+    taps: array<vec4<f32>, 51>,
+}
+@group(0) @binding(2) var<uniform> settings: GaussianBlurUniforms;
+
+// The segment and its apron on each side.
+var<workgroup> tile: array<vec4<f32>, #{TILE_SIZE}>;
+// Gaussian weights, indexed by the distance to the kernel center.
+var<workgroup> weights: array<f32, 201>;
+
+// Blurs along `axis` the pixel `local` of the segment `workgroup.x` of the line `workgroup.y` of the viewport.
+fn blur(workgroup: vec3<u32>, local: u32, axis: vec2<i32>) {
+    let texture_size = vec2<f32>(textureDimensions(source_texture));
+    let origin = vec2<i32>(round(settings.viewport.xy * texture_size));
+    let size = vec2<i32>(round(settings.viewport.zw * texture_size)) - origin;
+    let other_axis = vec2(1) - axis;
+    let length = dot(size, axis);
+    let line = i32(workgroup.y);
+    let radius = (settings.kernel_size - 1) / 2;
+    let start = i32(workgroup.x * WORKGROUP_SIZE) - radius;
+
+    // Edge pixels of the viewport are repeated, as with the clamped sampling of the fragment shaders.
+    for (var i = i32(local); i < i32(WORKGROUP_SIZE) + 2 * radius; i += i32(WORKGROUP_SIZE)) {
+        let position = clamp(start + i, 0, length - 1);
+        tile[i] = textureLoad(source_texture, origin + axis * position + other_axis * line, 0);
+    }
+    for (var i = i32(local); i <= radius; i += i32(WORKGROUP_SIZE)) {
+        weights[i] = exp(-f32(i * i) / (2.0 * settings.sigma * settings.sigma));
+    }
+    workgroupBarrier();
+
+    let position = i32(workgroup.x * WORKGROUP_SIZE + local);
+    if position >= length || line >= dot(size, other_axis) {
+        return;
+    }
+    let center = i32(local) + radius;
+    var color = weights[0] * tile[center];
+    var weight_sum = weights[0];
+    for (var i = 1; i <= radius; i++) {
+        color += weights[i] * (tile[center - i] + tile[center + i]);
+        weight_sum += 2.0 * weights[i];
+    }
+    textureStore(output_texture, origin + axis * position + other_axis * line, color / weight_sum);
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
+fn compute_horizontal(
+    @builtin(workgroup_id) workgroup: vec3<u32>,
+    @builtin(local_invocation_index) local: u32,
+) {
+    blur(workgroup, local, vec2(1, 0));
+}
+
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
+fn compute_vertical(
+    @builtin(workgroup_id) workgroup: vec3<u32>,
+    @builtin(local_invocation_index) local: u32,
+) {
+    blur(workgroup, local, vec2(0, 1));
+}
@@ -2,7 +2,7 @@ mod pipeline;
 mod settings;
 mod tweening;
 
-pub use settings::GaussianBlurSettings;
+pub use settings::{GaussianBlurBackend, GaussianBlurSettings, KernelSize};
 #[cfg(feature = "bevy_tweening")]
 pub use tweening::*;
 
@@ -14,17 +14,38 @@ use bevy::{
     },
     prelude::*,
     render::{
-        extract_component::{ExtractComponentPlugin, UniformComponentPlugin},
+        extract_component::UniformComponentPlugin,
         render_graph::{RenderGraphApp, ViewNodeRunner},
-        RenderApp,
+        render_resource::{
+            SpecializedRenderPipelines, TextureDescriptor, TextureDimension, TextureUsages,
+        },
+        renderer::RenderDevice,
+        texture::{CachedTexture, TextureCache},
+        view::ViewTarget,
+        Render, RenderApp, RenderSet,
     },
 };
 use pipeline::*;
 
+use crate::{
+    alpha_mode::load_alpha_mode_shader,
+    bind_groups::ViewBindGroups,
+    compute::{is_compute_supported, COMPUTE_TEXTURE_FORMAT},
+    edge_mode::load_edge_mode_shader,
+    extract::ExtractBlurSettingsPlugin,
+    transition::animate_blur_transitions,
+    BlurTransitionFinished,
+};
+
 use self::settings::GaussianBlurUniforms;
 
 const GAUSSIAN_BLUR_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(0x3794890ac6fb4a5f87a69411d39c8fc7);
+const GAUSSIAN_BLUR_COMPUTE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0xa6297262a53c45a6bf8f57d103cff404);
+
+// Number of pixels of a line (or column) blurred by a workgroup of the compute backend.
+const GAUSSIAN_BLUR_WORKGROUP_SIZE: u32 = 256;
 
 /// This plugins adds support for a gaussian blur post-processing effects to 2D or 3D cameras.
 ///
@@ -34,6 +55,7 @@ const GAUSSIAN_BLUR_SHADER_HANDLE: Handle<Shader> =
 /// Additional details on blur filters can be seen in this [Intel article](https://www.intel.com/content/www/us/en/developer/articles/technical/an-investigation-of-fast-real-time-gpu-based-image-blur-algorithms.html)
 ///
 /// This implementation is done with 2 post-processing passes.
+/// Those passes can also be done by compute shaders, see [`GaussianBlurBackend`].
 ///
 /// ```
 ///# use bevy::prelude::*;
@@ -58,10 +80,21 @@ impl Plugin for GaussianBlurPlugin {
             "gaussian_blur.wgsl",
             Shader::from_wgsl
         );
-        app.register_type::<GaussianBlurSettings>();
+        load_internal_asset!(
+            app,
+            GAUSSIAN_BLUR_COMPUTE_SHADER_HANDLE,
+            "gaussian_blur_compute.wgsl",
+            Shader::from_wgsl
+        );
+        app.register_type::<GaussianBlurSettings>()
+            .register_type::<GaussianBlurBackend>();
+        load_edge_mode_shader(app);
+        load_alpha_mode_shader(app);
+        app.add_event::<BlurTransitionFinished>()
+            .add_systems(Update, animate_blur_transitions::<GaussianBlurSettings>);
 
         app.add_plugins((
-            ExtractComponentPlugin::<GaussianBlurSettings>::default(),
+            ExtractBlurSettingsPlugin::<GaussianBlurSettings>::default(),
             UniformComponentPlugin::<GaussianBlurUniforms>::default(),
         ));
 
@@ -70,6 +103,15 @@ impl Plugin for GaussianBlurPlugin {
         };
 
         render_app
+            .init_resource::<ViewBindGroups<GaussianBlurPipeline>>()
+            .init_resource::<SpecializedRenderPipelines<GaussianBlurPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_gaussian_blur_pipelines.in_set(RenderSet::Prepare),
+                    prepare_gaussian_blur_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
             // Add gaussian blur to the 3d render graph;
             .add_render_graph_node::<ViewNodeRunner<GaussianBlurNode>>(Core3d, GaussianBlurLabel)
             .add_render_graph_edges(
@@ -98,5 +140,57 @@ impl Plugin for GaussianBlurPlugin {
         };
 
         render_app.init_resource::<GaussianBlurPipeline>();
+        warm_up_gaussian_blur_pipelines(&mut render_app.world);
+
+        // Without the compute pipeline, no view gets compute textures, and the fragment passes are used.
+        if is_compute_supported(&render_app.world, GAUSSIAN_BLUR_WORKGROUP_SIZE) {
+            render_app
+                .init_resource::<GaussianBlurComputePipeline>()
+                .init_resource::<ViewBindGroups<GaussianBlurComputePipeline>>()
+                .add_systems(
+                    Render,
+                    (
+                        prepare_gaussian_blur_compute_textures.in_set(RenderSet::PrepareResources),
+                        prepare_gaussian_blur_compute_bind_groups
+                            .in_set(RenderSet::PrepareBindGroups),
+                    ),
+                );
+        }
+    }
+}
+
+fn prepare_gaussian_blur_compute_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(Entity, &ViewTarget, &GaussianBlurBackend)>,
+) {
+    for (entity, view_target, backend) in &views {
+        if *backend != GaussianBlurBackend::Compute {
+            continue;
+        }
+        let texture_descriptor = TextureDescriptor {
+            label: Some("gaussian_blur_compute_texture"),
+            size: view_target.main_texture().size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: COMPUTE_TEXTURE_FORMAT,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        commands.entity(entity).insert(GaussianBlurComputeTextures {
+            horizontal: texture_cache.get(&render_device, texture_descriptor.clone()),
+            vertical: texture_cache.get(&render_device, texture_descriptor),
+        });
     }
 }
+
+// Storage textures written by the compute passes of a view.
+#[derive(Component)]
+pub(crate) struct GaussianBlurComputeTextures {
+    // Written by the horizontal pass, read by the vertical pass.
+    horizontal: CachedTexture,
+    // Written by the vertical pass, copied to the view target.
+    vertical: CachedTexture,
+}
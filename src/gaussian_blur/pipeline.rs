@@ -1,18 +1,26 @@
-use super::settings::GaussianBlurUniforms;
-use super::GAUSSIAN_BLUR_SHADER_HANDLE;
+use super::settings::{specialized_tap_count, GaussianBlurUniforms, KernelSize};
+use super::{
+    GaussianBlurComputeTextures, GaussianBlurSettings, GAUSSIAN_BLUR_COMPUTE_SHADER_HANDLE,
+    GAUSSIAN_BLUR_SHADER_HANDLE, GAUSSIAN_BLUR_WORKGROUP_SIZE,
+};
+use crate::{bind_groups::ViewBindGroups, compute::COMPUTE_TEXTURE_FORMAT};
 use bevy::{
     core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
     ecs::query::QueryItem,
     prelude::*,
     render::{
-        extract_component::ComponentUniforms,
+        camera::ExtractedCamera,
+        extract_component::{ComponentUniforms, DynamicUniformIndex},
         render_graph::{NodeRunError, RenderGraphContext, RenderLabel, ViewNode},
         render_resource::{
-            binding_types::{sampler, texture_2d, uniform_buffer},
-            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
-            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
-            SamplerBindingType, SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+            binding_types::{sampler, texture_2d, texture_storage_2d, uniform_buffer},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BindingResource,
+            CachedComputePipelineId, CachedRenderPipelineId, ComputePassDescriptor,
+            ComputePipelineDescriptor, FilterMode, FragmentState, MultisampleState, Operations,
+            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderDefVal,
+            ShaderStages, SpecializedRenderPipeline, SpecializedRenderPipelines,
+            StorageTextureAccess, TextureFormat, TextureSampleType, TextureView,
         },
         renderer::{RenderContext, RenderDevice},
         texture::BevyDefault,
@@ -25,30 +33,129 @@ pub(crate) struct GaussianBlurLabel;
 
 // The post process node used for the render graph
 #[derive(Default)]
-pub struct GaussianBlurNode;
+pub(crate) struct GaussianBlurNode;
 
 // The ViewNode trait is required by the ViewNodeRunner
 impl ViewNode for GaussianBlurNode {
     type ViewQuery = (
         &'static ViewTarget,
-        // This make sure the node is only run on cameras with an extracted GaussianBlurUniform component
-        &'static GaussianBlurUniforms,
+        &'static ExtractedCamera,
+        // This make sure the node is only run on cameras with an extracted GaussianBlurUniform component,
+        // the offset of its uniforms in the buffer shared by all the views
+        &'static DynamicUniformIndex<GaussianBlurUniforms>,
+        // Only prepared for cameras using the compute backend, when it is supported
+        Option<&'static GaussianBlurComputeTextures>,
+        &'static GaussianBlurPipelineIds,
     );
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, _gaussian_blur_uniforms): QueryItem<Self::ViewQuery>,
+        (view_target, camera, uniform_index, compute_textures, pipeline_ids): QueryItem<
+            Self::ViewQuery,
+        >,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let gaussian_blur_pipeline = world.resource::<GaussianBlurPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
-        let settings_uniforms = world.resource::<ComponentUniforms<GaussianBlurUniforms>>();
+        let bind_groups = world.resource::<ViewBindGroups<GaussianBlurPipeline>>();
+
+        let Some(bind_groups) = bind_groups.get(graph.view_entity()) else {
+            return Ok(());
+        };
+
+        // The fragment passes are used while the compute pipelines are not ready.
+        if let Some(compute_textures) = compute_textures {
+            let compute_pipeline = world.resource::<GaussianBlurComputePipeline>();
+            let compute_bind_groups =
+                world.resource::<ViewBindGroups<GaussianBlurComputePipeline>>();
+            if let (
+                Some(compute_bind_groups),
+                Some(horizontal_pipeline),
+                Some(vertical_pipeline),
+                Some(copy_pipeline),
+            ) = (
+                compute_bind_groups.get(graph.view_entity()),
+                pipeline_cache.get_compute_pipeline(compute_pipeline.horizontal_pipeline_id),
+                pipeline_cache.get_compute_pipeline(compute_pipeline.vertical_pipeline_id),
+                pipeline_cache.get_render_pipeline(gaussian_blur_pipeline.copy_pipeline_id),
+            ) {
+                render_context
+                    .command_encoder()
+                    .push_debug_group("gaussian_blur");
+
+                let post_process = view_target.post_process_write();
+                // Only the viewport of the textures is blurred.
+                let size = camera.physical_viewport_size.unwrap_or(UVec2::new(
+                    compute_textures.horizontal.texture.width(),
+                    compute_textures.horizontal.texture.height(),
+                ));
+                {
+                    let mut compute_pass = render_context.command_encoder().begin_compute_pass(
+                        &ComputePassDescriptor {
+                            label: Some("gaussian_blur_compute_pass"),
+                            timestamp_writes: None,
+                        },
+                    );
+                    // Each workgroup blurs a segment of a line, then of a column.
+                    compute_pass.set_pipeline(horizontal_pipeline);
+                    compute_pass.set_bind_group(
+                        0,
+                        compute_bind_groups.get(post_process.source),
+                        &[uniform_index.index()],
+                    );
+                    compute_pass.dispatch_workgroups(
+                        size.x.div_ceil(GAUSSIAN_BLUR_WORKGROUP_SIZE),
+                        size.y,
+                        1,
+                    );
+                    compute_pass.set_pipeline(vertical_pipeline);
+                    compute_pass.set_bind_group(
+                        0,
+                        compute_bind_groups.get(&compute_textures.horizontal.default_view),
+                        &[uniform_index.index()],
+                    );
+                    compute_pass.dispatch_workgroups(
+                        size.y.div_ceil(GAUSSIAN_BLUR_WORKGROUP_SIZE),
+                        size.x,
+                        1,
+                    );
+                }
+
+                // The storage texture is copied to the view target.
+                let mut render_pass =
+                    render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                        label: Some("gaussian_blur_copy_pass"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: post_process.destination,
+                            resolve_target: None,
+                            ops: Operations::default(),
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                if let Some(viewport) = &camera.viewport {
+                    render_pass.set_camera_viewport(viewport);
+                }
+                render_pass.set_render_pipeline(copy_pipeline);
+                render_pass.set_bind_group(
+                    0,
+                    bind_groups.get(&compute_textures.vertical.default_view),
+                    &[uniform_index.index()],
+                );
+                render_pass.draw(0..3, 0..1);
+                drop(render_pass);
+
+                render_context.command_encoder().pop_debug_group();
+
+                return Ok(());
+            }
+        }
 
-        let (Some(settings_binding), Some(horizontal_pipeline), Some(vertical_pipeline)) = (
-            settings_uniforms.uniforms().binding(),
-            pipeline_cache.get_render_pipeline(gaussian_blur_pipeline.horizontal_pipeline_id),
-            pipeline_cache.get_render_pipeline(gaussian_blur_pipeline.vertical_pipeline_id),
+        let (Some(horizontal_pipeline), Some(vertical_pipeline)) = (
+            pipeline_cache.get_render_pipeline(pipeline_ids.horizontal),
+            pipeline_cache.get_render_pipeline(pipeline_ids.vertical),
         ) else {
             return Ok(());
         };
@@ -60,19 +167,7 @@ impl ViewNode for GaussianBlurNode {
         for pipeline in [horizontal_pipeline, vertical_pipeline] {
             let post_process = view_target.post_process_write();
 
-            let bind_group = render_context.render_device().create_bind_group(
-                "gaussian_blur_bind_group",
-                &gaussian_blur_pipeline.layout,
-                // It's important for this to match the BindGroupLayout defined in the GaussianBlurPipeline
-                &BindGroupEntries::sequential((
-                    // Make sure to use the source view
-                    post_process.source,
-                    // Use the sampler created for the pipeline
-                    &gaussian_blur_pipeline.sampler,
-                    // Set the settings binding
-                    settings_binding.clone(),
-                )),
-            );
+            let bind_group = bind_groups.get(post_process.source);
 
             let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
                 label: Some("gaussian_blur_pass"),
@@ -88,8 +183,12 @@ impl ViewNode for GaussianBlurNode {
                 occlusion_query_set: None,
             });
 
+            // The other cameras sharing the render target keep their viewports.
+            if let Some(viewport) = &camera.viewport {
+                render_pass.set_camera_viewport(viewport);
+            }
             render_pass.set_render_pipeline(pipeline);
-            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_bind_group(0, bind_group, &[uniform_index.index()]);
             render_pass.draw(0..3, 0..1);
         }
 
@@ -100,11 +199,35 @@ impl ViewNode for GaussianBlurNode {
 }
 
 #[derive(Resource)]
-pub struct GaussianBlurPipeline {
+pub(crate) struct GaussianBlurPipeline {
     layout: BindGroupLayout,
     sampler: Sampler,
-    horizontal_pipeline_id: CachedRenderPipelineId,
-    vertical_pipeline_id: CachedRenderPipelineId,
+    copy_pipeline_id: CachedRenderPipelineId,
+}
+
+// The fragment passes are specialized on their direction and on their number of taps,
+// the loop on the taps having a constant bound.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct GaussianBlurPipelineKey {
+    vertical: bool,
+    // Quantized number of taps, the additional taps having a null weight.
+    tap_count: u32,
+}
+
+impl GaussianBlurPipelineKey {
+    fn new(vertical: bool, tap_count: u32) -> Self {
+        Self {
+            vertical,
+            tap_count: specialized_tap_count(tap_count),
+        }
+    }
+}
+
+// Specialized pipelines of the fragment passes of a view.
+#[derive(Component)]
+pub(crate) struct GaussianBlurPipelineIds {
+    horizontal: CachedRenderPipelineId,
+    vertical: CachedRenderPipelineId,
 }
 
 impl FromWorld for GaussianBlurPipeline {
@@ -118,50 +241,33 @@ impl FromWorld for GaussianBlurPipeline {
                 (
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
-                    uniform_buffer::<GaussianBlurUniforms>(false),
+                    uniform_buffer::<GaussianBlurUniforms>(true),
                 ),
             ),
         );
 
         // We can create the sampler here since it won't change at runtime and doesn't depend on the view
-        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
-
-        let horizontal_pipeline_id = world
-            .resource_mut::<PipelineCache>()
-            // This will add the pipeline to the cache and queue it's creation
-            .queue_render_pipeline(RenderPipelineDescriptor {
-                label: Some("gaussian_blur_horizontal_pipeline".into()),
-                layout: vec![layout.clone()],
-                vertex: fullscreen_shader_vertex_state(),
-                fragment: Some(FragmentState {
-                    shader: GAUSSIAN_BLUR_SHADER_HANDLE,
-                    shader_defs: vec![],
-                    entry_point: "fragment_horizontal".into(),
-                    targets: vec![Some(TextureFormat::bevy_default().into())],
-                }),
-                // All of the following properties are not important for this effect so just use the default values.
-                // This struct doesn't have the Default trait implemented because not all field can have a default value.
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                push_constant_ranges: vec![],
-            });
-        let vertical_pipeline_id =
+        // The taps rely on linear filtering to sample two pixels at once
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        // Copies the result of the compute backend to the view target.
+        let copy_pipeline_id =
             world
                 .resource_mut::<PipelineCache>()
                 .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some("gaussian_blur_vertical_pipeline".into()),
+                    label: Some("gaussian_blur_copy_pipeline".into()),
                     layout: vec![layout.clone()],
-                    // This will setup a fullscreen triangle for the vertex state
                     vertex: fullscreen_shader_vertex_state(),
                     fragment: Some(FragmentState {
                         shader: GAUSSIAN_BLUR_SHADER_HANDLE,
                         shader_defs: vec![],
-                        entry_point: "fragment_vertical".into(),
+                        entry_point: "fragment_copy".into(),
                         targets: vec![Some(TextureFormat::bevy_default().into())],
                     }),
-                    // All of the following properties are not important for this effect so just use the default values.
-                    // This struct doesn't have the Default trait implemented because not all field can have a default value.
                     primitive: PrimitiveState::default(),
                     depth_stencil: None,
                     multisample: MultisampleState::default(),
@@ -171,8 +277,243 @@ impl FromWorld for GaussianBlurPipeline {
         Self {
             layout,
             sampler,
+            copy_pipeline_id,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for GaussianBlurPipeline {
+    type Key = GaussianBlurPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let (label, entry_point) = if key.vertical {
+            ("gaussian_blur_vertical_pipeline", "fragment_vertical")
+        } else {
+            ("gaussian_blur_horizontal_pipeline", "fragment_horizontal")
+        };
+        RenderPipelineDescriptor {
+            label: Some(label.into()),
+            layout: vec![self.layout.clone()],
+            // This will setup a fullscreen triangle for the vertex state
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: GAUSSIAN_BLUR_SHADER_HANDLE,
+                shader_defs: vec![ShaderDefVal::UInt("TAP_COUNT".into(), key.tap_count)],
+                entry_point: entry_point.into(),
+                targets: vec![Some(TextureFormat::bevy_default().into())],
+            }),
+            // All of the following properties are not important for this effect so just use the default values.
+            // This struct doesn't have the Default trait implemented because not all field can have a default value.
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+pub(crate) fn prepare_gaussian_blur_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    gaussian_blur_pipeline: Res<GaussianBlurPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GaussianBlurPipeline>>,
+    views: Query<(Entity, &GaussianBlurUniforms)>,
+) {
+    for (entity, uniforms) in &views {
+        let [horizontal, vertical] = [false, true].map(|vertical| {
+            pipelines.specialize(
+                &pipeline_cache,
+                &gaussian_blur_pipeline,
+                GaussianBlurPipelineKey::new(vertical, uniforms.tap_count),
+            )
+        });
+        commands.entity(entity).insert(GaussianBlurPipelineIds {
+            horizontal,
+            vertical,
+        });
+    }
+}
+
+// Queues the pipelines of the settings for common sigmas, so that they are compiled before being used.
+pub(crate) fn warm_up_gaussian_blur_pipelines(world: &mut World) {
+    world.resource_scope(
+        |world, mut pipelines: Mut<SpecializedRenderPipelines<GaussianBlurPipeline>>| {
+            let pipeline_cache = world.resource::<PipelineCache>();
+            let gaussian_blur_pipeline = world.resource::<GaussianBlurPipeline>();
+            for sigma in [2.0, 5.0, 10.0, 20.0] {
+                let tap_count = GaussianBlurSettings::from_sigma(sigma).taps().len() as u32;
+                for vertical in [false, true] {
+                    pipelines.specialize(
+                        pipeline_cache,
+                        gaussian_blur_pipeline,
+                        GaussianBlurPipelineKey::new(vertical, tap_count),
+                    );
+                }
+            }
+        },
+    );
+}
+
+impl GaussianBlurPipeline {
+    // Creates the bind group of a pass sampling `source`.
+    fn create_bind_group(
+        &self,
+        render_device: &RenderDevice,
+        source: &TextureView,
+        settings_binding: BindingResource,
+    ) -> BindGroup {
+        render_device.create_bind_group(
+            "gaussian_blur_bind_group",
+            &self.layout,
+            // It's important for this to match the BindGroupLayout defined in the GaussianBlurPipeline
+            &BindGroupEntries::sequential((
+                // Make sure to use the source view
+                source,
+                // Use the sampler created for the pipeline
+                &self.sampler,
+                // Set the settings binding
+                settings_binding,
+            )),
+        )
+    }
+}
+
+// Each pass samples one of the main textures of the view, a bind group is prepared for both of them.
+// With the compute backend, the copy pass samples the texture written by the vertical compute pass.
+pub(crate) fn prepare_gaussian_blur_bind_groups(
+    render_device: Res<RenderDevice>,
+    gaussian_blur_pipeline: Res<GaussianBlurPipeline>,
+    settings_uniforms: Res<ComponentUniforms<GaussianBlurUniforms>>,
+    mut bind_groups: ResMut<ViewBindGroups<GaussianBlurPipeline>>,
+    views: Query<
+        (Entity, &ViewTarget, Option<&GaussianBlurComputeTextures>),
+        With<GaussianBlurUniforms>,
+    >,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) = (
+        settings_uniforms.uniforms().buffer(),
+        settings_uniforms.uniforms().binding(),
+    ) else {
+        return;
+    };
+    for (view, view_target, compute_textures) in &views {
+        let mut sources = vec![
+            view_target.main_texture_view(),
+            view_target.main_texture_other_view(),
+        ];
+        sources.extend(compute_textures.map(|textures| &textures.vertical.default_view));
+        bind_groups.update(view, &sources, Some(buffer.id()), |source| {
+            gaussian_blur_pipeline.create_bind_group(
+                &render_device,
+                source,
+                settings_binding.clone(),
+            )
+        });
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct GaussianBlurComputePipeline {
+    layout: BindGroupLayout,
+    horizontal_pipeline_id: CachedComputePipelineId,
+    vertical_pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for GaussianBlurComputePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "gaussian_blur_compute_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    texture_storage_2d(COMPUTE_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly),
+                    uniform_buffer::<GaussianBlurUniforms>(true),
+                ),
+            ),
+        );
+
+        // The shared memory holds a line segment and the highest kernel extent on each side.
+        let shader_defs = vec![
+            ShaderDefVal::UInt("WORKGROUP_SIZE".into(), GAUSSIAN_BLUR_WORKGROUP_SIZE),
+            ShaderDefVal::UInt(
+                "TILE_SIZE".into(),
+                GAUSSIAN_BLUR_WORKGROUP_SIZE + KernelSize::MAX - 1,
+            ),
+        ];
+        let mut queue_pipeline = |label: &'static str, entry_point: &'static str| {
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some(label.into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: GAUSSIAN_BLUR_COMPUTE_SHADER_HANDLE,
+                    shader_defs: shader_defs.clone(),
+                    entry_point: entry_point.into(),
+                })
+        };
+        let horizontal_pipeline_id = queue_pipeline(
+            "gaussian_blur_compute_horizontal_pipeline",
+            "compute_horizontal",
+        );
+        let vertical_pipeline_id = queue_pipeline(
+            "gaussian_blur_compute_vertical_pipeline",
+            "compute_vertical",
+        );
+
+        Self {
+            layout,
             horizontal_pipeline_id,
             vertical_pipeline_id,
         }
     }
 }
+
+// Each compute pass reads one texture and writes to one of the compute textures,
+// the horizontal pass reads one of the main textures and the vertical pass the result of the horizontal pass.
+pub(crate) fn prepare_gaussian_blur_compute_bind_groups(
+    render_device: Res<RenderDevice>,
+    compute_pipeline: Res<GaussianBlurComputePipeline>,
+    settings_uniforms: Res<ComponentUniforms<GaussianBlurUniforms>>,
+    mut bind_groups: ResMut<ViewBindGroups<GaussianBlurComputePipeline>>,
+    views: Query<(Entity, &ViewTarget, &GaussianBlurComputeTextures)>,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) = (
+        settings_uniforms.uniforms().buffer(),
+        settings_uniforms.uniforms().binding(),
+    ) else {
+        return;
+    };
+    for (view, view_target, textures) in &views {
+        let horizontal = &textures.horizontal.default_view;
+        let vertical = &textures.vertical.default_view;
+        // The bind group reading the horizontal texture writes to the vertical one, which is not a source.
+        bind_groups.update_binding(
+            view,
+            &[
+                view_target.main_texture_view(),
+                view_target.main_texture_other_view(),
+                horizontal,
+            ],
+            &[vertical],
+            Some(buffer.id()),
+            |source| {
+                let output = if source.id() == horizontal.id() {
+                    vertical
+                } else {
+                    horizontal
+                };
+                render_device.create_bind_group(
+                    "gaussian_blur_compute_bind_group",
+                    &compute_pipeline.layout,
+                    &BindGroupEntries::sequential((source, output, settings_binding.clone())),
+                )
+            },
+        );
+    }
+}
@@ -1,21 +1,33 @@
-use bevy::ecs::query::QueryItem;
 use bevy::prelude::*;
-use bevy::render::{extract_component::ExtractComponent, render_resource::ShaderType};
+use bevy::render::render_resource::ShaderType;
+
+use crate::{
+    extract::ExtractBlurSettings, viewport::viewport_uv_rect, BlurAlphaMode, BlurEdgeMode,
+    BlurQuality,
+};
 
 /// Applies a gaussian blur effect to a 2d or 3d camera in conjonction with the [`GaussianBlurPlugin`]
 ///
-#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
 #[reflect(Component, Default)]
 pub struct GaussianBlurSettings {
-    /// Kernel size for the computation of the gaussian blur
-    /// - It will be clamped to the range [1..401]
-    /// - It must be odd, else the first higher odd value will be used.
-    /// - A value of 1 correspond to no blur, and will entirely skip the post-processing effect.
-    /// - It defaults to 31 (sigma = 5).
-    /// The associated `sigma` value for the gaussian function will be computed as `(kernel_size-1)/6`, so that the kernel extends to  a `3*sigma` range.
+    /// Standard deviation of the gaussian function, in sampled pixels.
+    /// - It will be clamped to the range [0..100]
+    /// - A value of 0 correspond to no blur, and will entirely skip the post-processing effect.
+    /// - Non integer values are supported, allowing a smooth animation of the blur.
+    /// - It defaults to 5.
+    pub sigma: f32,
+    /// Kernel size for the computation of the gaussian blur.
+    /// - Defaults to [`KernelSize::Auto`], deriving the kernel size from `sigma` and `truncation`.
     ///
-    /// The computational cost of the gaussian blur post-processing effect is `2*kernel_size` texture sampling per pixels.
-    pub kernel_size: u32,
+    /// The weights are renormalized for the truncation of the gaussian function by the kernel.
+    /// The computational cost of the gaussian blur post-processing effect is about `kernel_size` texture sampling per pixels,
+    /// two adjacent pixels of the kernel being sampled at once.
+    pub kernel_size: KernelSize,
+    /// Number of `sigma` covered by each side of the kernel, when `kernel_size` is [`KernelSize::Auto`].
+    /// - It will be clamped to the range [1..6]
+    /// - It defaults to 3, 4 giving a more accurate gaussian blur for a higher cost.
+    pub truncation: f32,
     /// A factor that is applied whenever the post-processing effect is sampling for a distant pixel.
     /// This can be used to create a bigger blur without impacting the computational cost, but sacrificing quality.
     /// - It will be clamped to the range [1..100]
@@ -23,69 +35,350 @@ pub struct GaussianBlurSettings {
     /// - Defaults to 1, which is the neutral value, not impacting the algorithm.
     ///
     pub sampling_distance_factor: f32,
+    /// Defines how the blur is computed on the GPU.
+    /// - Defaults to [`GaussianBlurBackend::Fragment`]
+    pub backend: GaussianBlurBackend,
+    /// Defines how the pixels beyond the edges of the view are sampled.
+    /// - Defaults to [`BlurEdgeMode::Clamp`]
+    ///
+    /// The compute backend is only used with [`BlurEdgeMode::Clamp`].
+    pub edge_mode: BlurEdgeMode,
+    /// Defines how the alpha channel is blurred, for cameras rendering to a transparent target.
+    /// - Defaults to [`BlurAlphaMode::Straight`]
+    ///
+    /// The compute backend is only used with [`BlurAlphaMode::Straight`].
+    pub alpha_mode: BlurAlphaMode,
 }
 impl Default for GaussianBlurSettings {
     fn default() -> Self {
         Self {
-            kernel_size: 31,
+            sigma: 5.0,
+            kernel_size: KernelSize::Auto,
+            truncation: 3.0,
             sampling_distance_factor: 1.,
+            backend: GaussianBlurBackend::Fragment,
+            edge_mode: BlurEdgeMode::Clamp,
+            alpha_mode: BlurAlphaMode::Straight,
         }
     }
 }
+
+/// Backend computing the [`GaussianBlurSettings`] on the GPU.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GaussianBlurBackend {
+    /// Each pass is a fragment shader sampling the whole kernel for each pixel.
+    #[default]
+    Fragment,
+    /// Each pass is a compute shader loading the pixels covered by a workgroup,
+    /// with the kernel extent on each side, in workgroup shared memory.
+    /// Each pixel is then only read once from the texture by the workgroup, which is faster for big kernels.
+    ///
+    /// The result is written to a storage texture before being copied to the view target.
+    /// This falls back to [`GaussianBlurBackend::Fragment`] when compute shaders or storage textures
+    /// are not supported, for example on WebGL2, when the `sampling_distance_factor` is not 1,
+    /// when the `edge_mode` is not [`BlurEdgeMode::Clamp`] and when the `alpha_mode` is not [`BlurAlphaMode::Straight`].
+    ///
+    /// The cost reported by [`BlurSetting::sampling_per_pixel`](crate::BlurSetting::sampling_per_pixel)
+    /// assumes compute shaders are supported, and underestimates the fragment fallback otherwise.
+    Compute,
+}
+
+/// Kernel size of the [`GaussianBlurSettings`].
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KernelSize {
+    /// The kernel size is the first odd value covering `truncation*sigma` on each side.
+    #[default]
+    Auto,
+    /// Fixed kernel size.
+    /// - It will be clamped to the range [1..401]
+    /// - It must be odd, else the first higher odd value will be used.
+    /// - A value of 1 correspond to no blur, and will entirely skip the post-processing effect.
+    Fixed(u32),
+}
+impl KernelSize {
+    // Highest legal kernel size.
+    pub(crate) const MAX: u32 = 401;
+
+    /// Provides the legal kernel size, `Auto` being computed for the given `sigma` and `truncation`.
+    pub fn value(&self, sigma: f32, truncation: f32) -> u32 {
+        let kernel_size = match self {
+            KernelSize::Auto => 2 * (truncation * sigma).ceil() as u32 + 1,
+            KernelSize::Fixed(kernel_size) => *kernel_size,
+        };
+        let kernel_size = kernel_size.clamp(1, Self::MAX);
+        if kernel_size.is_multiple_of(2) {
+            kernel_size + 1
+        } else {
+            kernel_size
+        }
+    }
+}
+
 impl crate::BlurSetting for GaussianBlurSettings {
     const NO_BLUR: GaussianBlurSettings = GaussianBlurSettings {
-        kernel_size: 1,
+        sigma: 0.0,
+        kernel_size: KernelSize::Auto,
+        truncation: 3.0,
         sampling_distance_factor: 1.,
+        backend: GaussianBlurBackend::Fragment,
+        edge_mode: BlurEdgeMode::Clamp,
+        alpha_mode: BlurAlphaMode::Straight,
     };
 
     fn sampling_per_pixel(&self) -> f32 {
-        match self.kernel_size {
+        let settings = self.create_concrete();
+        match settings.concrete_kernel_size() {
             1 => 0.,
-            k => (2 * k) as f32,
+            // Each workgroup loads its pixels and the kernel extent on each side once,
+            // then the result is copied to the view target.
+            // The GPU support is only known by the render app, so the compute backend is assumed to run.
+            k if settings.uses_compute_shader() => {
+                2. * (1. + (k - 1) as f32 / super::GAUSSIAN_BLUR_WORKGROUP_SIZE as f32) + 1.
+            }
+            // Adjacent pixels of the kernel are merged, on each side of the center.
+            k => (2 * (2 * specialized_tap_count(1 + (k / 2).div_ceil(2)) - 1)) as f32,
         }
     }
 
     fn passes(&self) -> u32 {
-        match self.kernel_size {
+        let settings = self.create_concrete();
+        match settings.concrete_kernel_size() {
             1 => 0,
+            // The copy to the view target is an additional pass.
+            _ if settings.uses_compute_shader() => 3,
             _ => 2,
         }
     }
+
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64 {
+        let pixels = viewport_size.x as f64 * viewport_size.y as f64;
+        (pixels * self.sampling_per_pixel() as f64).round() as u64
+    }
+
+    fn equivalent_sigma(&self) -> f32 {
+        // Standard deviation of the truncated and renormalized gaussian kernel.
+        let settings = self.create_concrete();
+        let upper = (settings.concrete_kernel_size() as i32 - 1) / 2;
+        if upper == 0 || settings.sigma == 0.0 {
+            return 0.0;
+        }
+        let weight = |x: i32| (-(x * x) as f32 / (2.0 * settings.sigma.powi(2))).exp();
+        let weight_sum: f32 = (-upper..=upper).map(weight).sum();
+        let variance: f32 = (-upper..=upper)
+            .map(|x| weight(x) * (x * x) as f32)
+            .sum::<f32>()
+            / weight_sum;
+        variance.sqrt() * settings.sampling_distance_factor
+    }
+
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let (start, end) = (self.create_concrete(), other.create_concrete());
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        // Automatic kernel sizes stay automatic, following the interpolated sigma.
+        let kernel_size = match (self.kernel_size, other.kernel_size) {
+            (KernelSize::Auto, KernelSize::Auto) => KernelSize::Auto,
+            _ => KernelSize::Fixed(crate::lerp_kernel_size(
+                start.concrete_kernel_size(),
+                end.concrete_kernel_size(),
+                t,
+            )),
+        };
+        GaussianBlurSettings {
+            sigma: lerp(start.sigma, end.sigma),
+            kernel_size,
+            truncation: lerp(start.truncation, end.truncation),
+            sampling_distance_factor: lerp(
+                start.sampling_distance_factor,
+                end.sampling_distance_factor,
+            ),
+            backend: if t < 0.5 { self.backend } else { other.backend },
+            edge_mode: if t < 0.5 {
+                self.edge_mode
+            } else {
+                other.edge_mode
+            },
+            alpha_mode: if t < 0.5 {
+                self.alpha_mode
+            } else {
+                other.alpha_mode
+            },
+        }
+        .create_concrete()
+    }
 }
 impl GaussianBlurSettings {
+    /// Computes the `GaussianBlurSettings` for a gaussian function of the given `sigma`.
+    ///
+    /// The kernel size is automatic, extending to a `3*sigma` range,
+    /// when it would be higher than 401 the `sampling_distance_factor` is increased instead.
+    pub fn from_sigma(sigma: f32) -> GaussianBlurSettings {
+        let sigma = sigma.max(0.0);
+        let sampling_distance_factor = (3.0 * sigma / 200.0).clamp(1.0, 100.0);
+        GaussianBlurSettings {
+            sigma: sigma / sampling_distance_factor,
+            sampling_distance_factor,
+            ..default()
+        }
+    }
+    /// Computes the settings used for a [`BlurQuality`] by the default [`BlurQualityMapping`](crate::BlurQualityMapping),
+    /// with about the same equivalent sigma.
+    ///
+    /// - [`BlurQuality::Low`] and [`BlurQuality::Medium`] divide the kernel size by 3 and 1.5,
+    ///   multiplying the `sampling_distance_factor` accordingly.
+    /// - [`BlurQuality::High`] keeps the settings.
+    /// - [`BlurQuality::Ultra`] extends the automatic kernel size to a `truncation` of at least 4.
+    pub fn with_quality(&self, quality: BlurQuality) -> GaussianBlurSettings {
+        let spacing = match quality {
+            BlurQuality::Low => 3.0,
+            BlurQuality::Medium => 1.5,
+            BlurQuality::High => return *self,
+            BlurQuality::Ultra => {
+                return GaussianBlurSettings {
+                    truncation: self.truncation.max(4.0),
+                    ..*self
+                }
+            }
+        };
+        let settings = self.create_concrete();
+        GaussianBlurSettings {
+            sigma: settings.sigma / spacing,
+            kernel_size: match settings.kernel_size {
+                KernelSize::Auto => KernelSize::Auto,
+                KernelSize::Fixed(kernel_size) => {
+                    KernelSize::Fixed(crate::scale_kernel_size(kernel_size, 1.0 / spacing))
+                }
+            },
+            sampling_distance_factor: settings.sampling_distance_factor * spacing,
+            ..settings
+        }
+    }
     /// Computes a new `GaussianBlurSettings` where each attribute is legal as expected by the shader.
     ///
-    /// It also replaces `KernelSize::Auto` by `KernelSize::Val(v)` where v is the first odd value higher than `4.*sigma`.
+    /// It keeps [`KernelSize::Auto`], see [`GaussianBlurSettings::concrete_kernel_size`] for the computed value.
     pub fn create_concrete(&self) -> GaussianBlurSettings {
-        let mut kernel_size = self.kernel_size.clamp(1, 401);
-        if kernel_size % 2 == 0 {
-            kernel_size += 1;
-        }
         GaussianBlurSettings {
-            kernel_size,
+            sigma: self.sigma.clamp(0.0, 100.0),
+            kernel_size: match self.kernel_size {
+                KernelSize::Auto => KernelSize::Auto,
+                kernel_size => KernelSize::Fixed(kernel_size.value(self.sigma, self.truncation)),
+            },
+            truncation: self.truncation.clamp(1.0, 6.0),
             sampling_distance_factor: self.sampling_distance_factor.clamp(1.0, 100.0),
+            backend: self.backend,
+            edge_mode: self.edge_mode,
+            alpha_mode: self.alpha_mode,
+        }
+    }
+    // Whether the compute backend can be used for these settings, when supported by the GPU.
+    pub(crate) fn uses_compute_shader(&self) -> bool {
+        self.backend == GaussianBlurBackend::Compute
+            && self.sampling_distance_factor == 1.0
+            && self.edge_mode == BlurEdgeMode::Clamp
+            && self.alpha_mode == BlurAlphaMode::Straight
+    }
+    // Taps sampled by each fragment pass, as `(offset, weight)` in sampled pixels, the center tap first.
+    //
+    // The weights are normalized over the whole kernel, each other tap being sampled on both sides of the center.
+    // Two adjacent pixels of the kernel are merged in a single tap placed at their weighted midpoint,
+    // linear filtering then weighting each of them as the kernel does.
+    pub(crate) fn taps(&self) -> Vec<Vec2> {
+        let settings = self.create_concrete();
+        let upper = (settings.concrete_kernel_size() as i32 - 1) / 2;
+        let weight = |x: i32| (-(x * x) as f32 / (2.0 * settings.sigma.powi(2))).exp();
+        let weight_sum: f32 = (-upper..=upper).map(weight).sum();
+        let weight = |x: i32| weight(x) / weight_sum;
+        let mut taps = vec![Vec2::new(0.0, weight(0))];
+        for a in (1..=upper).step_by(2) {
+            let (weight_a, weight_b) = (weight(a), if a < upper { weight(a + 1) } else { 0.0 });
+            let weight = weight_a + weight_b;
+            let offset = if weight > 0.0 {
+                a as f32 + weight_b / weight
+            } else {
+                a as f32
+            };
+            taps.push(Vec2::new(offset, weight));
         }
+        taps
+    }
+    /// Provides the legal kernel size used by the shader, computing it when [`KernelSize::Auto`].
+    pub fn concrete_kernel_size(&self) -> u32 {
+        let settings = self.create_concrete();
+        settings
+            .kernel_size
+            .value(settings.sigma, settings.truncation)
     }
 }
 
-impl ExtractComponent for GaussianBlurSettings {
-    type QueryData = &'static Self;
+impl ExtractBlurSettings for GaussianBlurSettings {
+    type Out = (GaussianBlurUniforms, GaussianBlurBackend);
 
-    type QueryFilter = ();
-    type Out = GaussianBlurUniforms;
+    // Sigma and the kernel grow up to their highest values, the sampling distance factor growing beyond.
+    fn scaled(&self, factor: f32) -> Self {
+        let settings = self.create_concrete();
+        let radius = settings.concrete_kernel_size() / 2;
+        if radius == 0 || settings.sigma == 0.0 {
+            return settings;
+        }
+        let sigma_scale = factor
+            .min(100.0 / settings.sigma)
+            .min(200.0 / radius as f32);
+        GaussianBlurSettings {
+            sigma: settings.sigma * sigma_scale,
+            kernel_size: match settings.kernel_size {
+                KernelSize::Auto => KernelSize::Auto,
+                KernelSize::Fixed(kernel_size) => {
+                    KernelSize::Fixed(crate::scale_kernel_size(kernel_size, sigma_scale))
+                }
+            },
+            sampling_distance_factor: settings.sampling_distance_factor * factor / sigma_scale,
+            ..settings
+        }
+    }
+
+    fn map_quality(&self, quality: BlurQuality) -> Self {
+        self.with_quality(quality)
+    }
+
+    fn capped(&self, max_sigma: f32) -> Self {
+        crate::extract::capped_to_sigma(self, max_sigma)
+    }
 
-    fn extract_component(settings: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
-        let settings = settings.create_concrete();
-        if settings.kernel_size == 1 {
+    fn extract(&self, camera: &Camera) -> Option<Self::Out> {
+        let settings = self.create_concrete();
+        let kernel_size = settings.concrete_kernel_size();
+        if kernel_size == 1 || settings.sigma == 0.0 {
             None
         } else {
-            let sigma = (settings.kernel_size - 1) as f32 / 6.0;
-            Some(GaussianBlurUniforms {
-                sigma,
-                kernel_size: settings.kernel_size,
-                sampling_distance_factor: settings.sampling_distance_factor,
-                _webgl2_padding: 0.,
-            })
+            let taps = settings.taps();
+            // Two taps are packed in each vector.
+            let mut packed_taps = [Vec4::ZERO; GAUSSIAN_BLUR_PACKED_TAPS];
+            for (packed, pair) in packed_taps.iter_mut().zip(taps.chunks(2)) {
+                *packed = pair[0].extend(0.0).extend(0.0);
+                if let Some(tap) = pair.get(1) {
+                    packed.z = tap.x;
+                    packed.w = tap.y;
+                }
+            }
+            Some((
+                GaussianBlurUniforms {
+                    viewport: viewport_uv_rect(
+                        camera.viewport.as_ref(),
+                        camera.physical_target_size(),
+                    ),
+                    sigma: settings.sigma,
+                    kernel_size,
+                    sampling_distance_factor: settings.sampling_distance_factor,
+                    tap_count: taps.len() as u32,
+                    edge_mode: settings.edge_mode.shader_value(),
+                    alpha_mode: settings.alpha_mode.shader_value(),
+                    taps: packed_taps,
+                },
+                if settings.uses_compute_shader() {
+                    GaussianBlurBackend::Compute
+                } else {
+                    GaussianBlurBackend::Fragment
+                },
+            ))
         }
     }
 }
@@ -94,12 +387,120 @@ impl ExtractComponent for GaussianBlurSettings {
 /// Will be available for use in the gaussian blur shader.
 #[derive(Component, ShaderType, Clone)]
 pub struct GaussianBlurUniforms {
+    // UV rectangle of the viewport of the camera in the view target
+    pub viewport: Vec4,
     // Legalized kernel size.
     pub kernel_size: u32,
-    // Computed sigma value based on kernel_size
+    // Legalized sigma
     pub sigma: f32,
     // Legalized sampling_distance_factor
     pub sampling_distance_factor: f32,
-    // webgl2 requires 16B padding
-    pub _webgl2_padding: f32,
+    // Number of taps, the center tap included
+    pub tap_count: u32,
+    // Value of the legalized edge_mode
+    pub edge_mode: u32,
+    // Value of the alpha_mode
+    pub alpha_mode: u32,
+    // Offsets and normalized weights of the taps, packed by two
+    pub taps: [Vec4; GAUSSIAN_BLUR_PACKED_TAPS],
+}
+
+// Highest number of taps: the center tap and up to 100 taps on each side.
+pub(crate) const GAUSSIAN_BLUR_MAX_TAPS: u32 = 101;
+// Number of taps of the fragment pipelines specialized for `tap_count` taps, the additional taps having a null weight.
+pub(crate) fn specialized_tap_count(tap_count: u32) -> u32 {
+    crate::quantize_loop_count(tap_count).min(GAUSSIAN_BLUR_MAX_TAPS)
+}
+// Length of the packed taps array, two taps per vector.
+const GAUSSIAN_BLUR_PACKED_TAPS: usize = GAUSSIAN_BLUR_MAX_TAPS.div_ceil(2) as usize;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Normalized weight of the pixel at `x` in the kernel of `settings`.
+    fn kernel_weight(settings: &GaussianBlurSettings, x: i32) -> f32 {
+        let upper = (settings.concrete_kernel_size() as i32 - 1) / 2;
+        let weight = |x: i32| (-(x * x) as f32 / (2.0 * settings.sigma.powi(2))).exp();
+        weight(x) / (-upper..=upper).map(weight).sum::<f32>()
+    }
+
+    #[test]
+    fn merged_taps_weights_sum_to_one() {
+        for sigma in [0.5, 2.0, 5.0, 30.0] {
+            let taps = GaussianBlurSettings::from_sigma(sigma).taps();
+            // The side taps are sampled on both sides of the center.
+            let sum = taps[0].y + 2.0 * taps[1..].iter().map(|tap| tap.y).sum::<f32>();
+            assert!((sum - 1.0).abs() < 1e-5, "{sum}");
+        }
+    }
+
+    #[test]
+    fn highest_kernel_size_uses_all_the_taps() {
+        let settings = GaussianBlurSettings {
+            sigma: 100.0,
+            kernel_size: KernelSize::Fixed(401),
+            ..default()
+        };
+        assert_eq!(settings.taps().len() as u32, GAUSSIAN_BLUR_MAX_TAPS);
+    }
+
+    #[test]
+    fn merged_taps_are_at_the_weighted_midpoints() {
+        // A radius of 5 leaves the pixel at 5 alone in the last tap.
+        let settings = GaussianBlurSettings {
+            sigma: 3.0,
+            kernel_size: KernelSize::Fixed(11),
+            ..default()
+        };
+        let taps = settings.taps();
+        assert_eq!(taps.len(), 4);
+        assert!((taps[0].y - kernel_weight(&settings, 0)).abs() < 1e-6);
+        for (i, tap) in taps.iter().enumerate().skip(1) {
+            let a = 2 * i as i32 - 1;
+            let weight_a = kernel_weight(&settings, a);
+            let weight_b = if a < 5 {
+                kernel_weight(&settings, a + 1)
+            } else {
+                0.0
+            };
+            let offset = (a as f32 * weight_a + (a + 1) as f32 * weight_b) / (weight_a + weight_b);
+            assert!((tap.x - offset).abs() < 1e-5, "{tap} instead of {offset}");
+            assert!((tap.y - (weight_a + weight_b)).abs() < 1e-6);
+        }
+        assert_eq!(taps[3].x, 5.0);
+    }
+
+    #[test]
+    fn auto_kernel_size_covers_the_truncation() {
+        assert_eq!(KernelSize::Auto.value(5.0, 3.0), 31);
+        assert_eq!(KernelSize::Fixed(30).value(5.0, 3.0), 31);
+        let settings = |truncation| GaussianBlurSettings {
+            sigma: 5.0,
+            truncation,
+            ..default()
+        };
+        assert_eq!(settings(3.0).concrete_kernel_size(), 31);
+        // The truncation is clamped to [1..6].
+        assert_eq!(settings(0.1).concrete_kernel_size(), 11);
+        assert_eq!(settings(10.0).concrete_kernel_size(), 61);
+    }
+
+    #[test]
+    fn truncated_weights_are_renormalized() {
+        let taps = |truncation| {
+            GaussianBlurSettings {
+                sigma: 5.0,
+                truncation,
+                ..default()
+            }
+            .taps()
+        };
+        let (truncated, wide) = (taps(1.5), taps(3.0));
+        assert!(truncated.len() < wide.len());
+        let sum = truncated[0].y + 2.0 * truncated[1..].iter().map(|tap| tap.y).sum::<f32>();
+        assert!((sum - 1.0).abs() < 1e-5, "{sum}");
+        // The weight of the truncated pixels is spread over the kept ones.
+        assert!(truncated[0].y > wide[0].y);
+    }
 }
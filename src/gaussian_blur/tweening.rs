@@ -1,15 +1,15 @@
 #![cfg(feature = "bevy_tweening")]
 use bevy_tweening::Lens;
 
-use crate::GaussianBlurSettings;
+use crate::{BlurSetting, GaussianBlurSettings};
 
 /// A `bevy_tweening` Lens implementation to allow animation of the gaussian blur.
 ///
-/// This will tweens the `sigma`, `kernel_size`, and `texel_step` attributes
-/// of the [`GaussianBlurSettings`].
+/// This will tweens the `sigma`, `kernel_size`, `truncation` and `sampling_distance_factor` attributes
+/// of the [`GaussianBlurSettings`], see [`BlurSetting::interpolate`].
 /// # Example
 ///
-/// System that will create an animation from a no blur to a  10-sigma blur effect
+/// System that will create an animation from a no blur to the default blur effect
 ///
 /// ```
 ///# use bevy::prelude::*;
@@ -17,7 +17,7 @@ use crate::GaussianBlurSettings;
 ///# use bevy_camera_blur::*;
 ///# use std::time::Duration;
 ///
-///pub fn blur(
+///fn blur(
 ///    mut commands: Commands,
 ///    camera: Query<Entity, (With<Camera>, With<GaussianBlurSettings>)>,
 ///) {
@@ -34,30 +34,18 @@ use crate::GaussianBlurSettings;
 ///}
 /// ```
 pub struct GaussianBlurLens {
-    /// Gaussian blur settings at the end of the tweening
+    /// Gaussian blur settings at the start of the tweening
     start: GaussianBlurSettings,
     /// Gaussian blur settings at the end of the tweening
     end: GaussianBlurSettings,
 }
 impl crate::BlurSettingLens<GaussianBlurSettings> for GaussianBlurLens {
     fn new(start: GaussianBlurSettings, end: GaussianBlurSettings) -> Self {
-        GaussianBlurLens {
-            start: start.create_concrete(),
-            end: end.create_concrete(),
-        }
+        GaussianBlurLens { start, end }
     }
 }
 impl Lens<GaussianBlurSettings> for GaussianBlurLens {
     fn lerp(&mut self, target: &mut GaussianBlurSettings, ratio: f32) {
-        let v1 = self.start.kernel_size as f32;
-        let v2 = self.end.kernel_size as f32;
-        target.kernel_size = {
-            let v = (v1 + (v2 - v1) * ratio).round() as u32;
-            if v % 2 == 0 {
-                v + 1
-            } else {
-                v
-            }
-        };
+        *target = self.start.interpolate(&self.end, ratio);
     }
 }
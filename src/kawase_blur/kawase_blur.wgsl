@@ -1,25 +1,41 @@
 // This shader computes the one pass of the kawase blur effect
 
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_camera_blur::edge_mode::{edge_coverage, edge_normalize, edge_sample, viewport_texel_size, viewport_uv}
+#import bevy_camera_blur::alpha_mode::{alpha_finish, alpha_premultiply}
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
 struct KawaseBlurUniforms {
     /// sampling distance for this pass of the Kawase filter
     sampling_distance: f32,
+    // Value of the `BlurEdgeMode`
+    edge_mode: u32,
+    // Value of the `BlurAlphaMode`
+    alpha_mode: u32,
     // WebGL2 structs must be 16 byte aligned.
-    _webgl2_padding: vec3<f32>,
+    _webgl2_padding: f32,
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    // The UV coordinates of the passes are relative to it, the render passes being restricted to the viewport.
+    viewport: vec4<f32>,
 }
 @group(0) @binding(2) var<uniform> settings: KawaseBlurUniforms;
 
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let texture_size = vec2<f32>(textureDimensions(screen_texture));
-    let texel_size = 1.0 / texture_size;
+    let texel_size = viewport_texel_size(screen_texture, settings.viewport);
     let d = settings.sampling_distance + 0.5;
-    var color = textureSample(screen_texture, texture_sampler, in.uv + vec2(d, d) * texel_size);
-    color += textureSample(screen_texture, texture_sampler, in.uv + vec2(-d, d) * texel_size);
-    color += textureSample(screen_texture, texture_sampler, in.uv + vec2(-d, -d) * texel_size);
-    color += textureSample(screen_texture, texture_sampler, in.uv + vec2(d, -d) * texel_size);
-    return color / 4.0;
+    var color = vec4(0.0);
+    var coverage = 0.0;
+    for (var i = 0; i < 4; i++) {
+        // The four corners (d, d), (-d, d), (-d, -d) and (d, -d).
+        let corner = vec2(select(-d, d, i == 0 || i == 3), select(-d, d, i < 2));
+        let uv = in.uv + corner * texel_size;
+        let sample = edge_sample(screen_texture, texture_sampler, uv, settings.viewport, settings.edge_mode);
+        color += 0.25 * alpha_premultiply(sample, settings.alpha_mode);
+        coverage += 0.25 * edge_coverage(uv, settings.edge_mode);
+    }
+    let blurred = edge_normalize(color, coverage, settings.edge_mode);
+    let texture_uv = viewport_uv(screen_texture, in.uv, settings.viewport);
+    return alpha_finish(blurred, screen_texture, texture_sampler, texture_uv, settings.alpha_mode);
 }
\ No newline at end of file
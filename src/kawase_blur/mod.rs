@@ -1,7 +1,12 @@
 mod pipeline;
 mod settings;
+mod tweening;
 
+pub(crate) use settings::kawase_pass_variance;
 pub use settings::KawaseBlurSettings;
+use settings::KawaseBlurUniforms;
+#[cfg(feature = "bevy_tweening")]
+pub use tweening::*;
 
 use bevy::{
     asset::load_internal_asset,
@@ -11,13 +16,21 @@ use bevy::{
     },
     prelude::*,
     render::{
-        extract_component::ExtractComponentPlugin,
+        camera::ExtractedCamera,
         render_graph::{RenderGraphApp, ViewNodeRunner},
-        RenderApp,
+        render_resource::DynamicUniformBuffer,
+        renderer::{RenderDevice, RenderQueue},
+        Render, RenderApp, RenderSet,
     },
 };
 use pipeline::*;
 
+use crate::{
+    alpha_mode::load_alpha_mode_shader, bind_groups::ViewBindGroups,
+    edge_mode::load_edge_mode_shader, extract::ExtractBlurSettingsPlugin,
+    transition::animate_blur_transitions, viewport::viewport_uv_rect, BlurTransitionFinished,
+};
+
 const KAWASE_BLUR_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(0x25a6854386ee40c28864d2e724268b7a);
 
@@ -55,14 +68,27 @@ impl Plugin for KawaseBlurPlugin {
             Shader::from_wgsl
         );
         app.register_type::<KawaseBlurSettings>();
+        load_edge_mode_shader(app);
+        load_alpha_mode_shader(app);
+        app.add_event::<BlurTransitionFinished>()
+            .add_systems(Update, animate_blur_transitions::<KawaseBlurSettings>);
 
-        app.add_plugins((ExtractComponentPlugin::<KawaseBlurSettings>::default(),));
+        app.add_plugins((ExtractBlurSettingsPlugin::<KawaseBlurSettings>::default(),));
 
         let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
         render_app
+            .init_resource::<KawaseBlurUniformBuffer>()
+            .init_resource::<ViewBindGroups<KawaseBlurPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_kawase_blur_uniforms.in_set(RenderSet::PrepareResources),
+                    prepare_kawase_blur_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
             // Add kawase blur to the 3d render graph;
             .add_render_graph_node::<ViewNodeRunner<KawaseBlurNode>>(Core3d, KawaseBlurLabel)
             .add_render_graph_edges(
@@ -93,3 +119,88 @@ impl Plugin for KawaseBlurPlugin {
         render_app.init_resource::<KawaseBlurPipeline>();
     }
 }
+
+// Uniforms of all the passes of all the views, each pass binding its own offset.
+#[derive(Resource, Default)]
+pub(crate) struct KawaseBlurUniformBuffer(DynamicUniformBuffer<KawaseBlurUniforms>);
+
+// Offsets in the `KawaseBlurUniformBuffer` of each pass of a view, in the order of the sampling distances.
+#[derive(Component)]
+pub(crate) struct KawaseBlurUniformOffsets(Vec<u32>);
+
+fn prepare_kawase_blur_uniforms(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut uniform_buffer: ResMut<KawaseBlurUniformBuffer>,
+    views: Query<(Entity, &KawaseBlurSettings, &ExtractedCamera)>,
+) {
+    uniform_buffer.0.clear();
+    for (entity, settings, camera) in &views {
+        let viewport = viewport_uv_rect(camera.viewport.as_ref(), camera.physical_target_size);
+        let offsets = push_pass_uniforms(settings, viewport, |uniforms| {
+            uniform_buffer.0.push(uniforms)
+        });
+        commands
+            .entity(entity)
+            .insert(KawaseBlurUniformOffsets(offsets));
+    }
+    uniform_buffer.0.write_buffer(&render_device, &render_queue);
+}
+
+// Pushes the uniforms of each pass, in the order of the sampling distances, returning their offsets.
+fn push_pass_uniforms(
+    settings: &KawaseBlurSettings,
+    viewport: Vec4,
+    mut push: impl FnMut(&KawaseBlurUniforms) -> u32,
+) -> Vec<u32> {
+    settings
+        .sampling_distances
+        .iter()
+        .map(|&sampling_distance| {
+            push(&KawaseBlurUniforms {
+                sampling_distance,
+                edge_mode: settings.edge_mode.shader_value(),
+                alpha_mode: settings.alpha_mode.shader_value(),
+                viewport,
+                ..default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::render_resource::encase;
+
+    #[test]
+    fn pass_offsets_follow_sampling_distances() {
+        let mut buffer = encase::DynamicUniformBuffer::new(Vec::<u8>::new());
+        let mut push = |uniforms: &KawaseBlurUniforms| buffer.write(uniforms).unwrap() as u32;
+        // Another view already using the buffer.
+        let other_offsets = push_pass_uniforms(
+            &KawaseBlurSettings {
+                sampling_distances: vec![5.0],
+                ..default()
+            },
+            Vec4::new(0.0, 0.0, 1.0, 1.0),
+            &mut push,
+        );
+        let settings = KawaseBlurSettings {
+            sampling_distances: vec![0.0, 1.0, 2.5, 2.0, 3.0],
+            ..default()
+        };
+        let offsets = push_pass_uniforms(&settings, Vec4::new(0.0, 0.0, 1.0, 1.0), &mut push);
+
+        assert_eq!(offsets.len(), settings.sampling_distances.len());
+        assert!(offsets.iter().all(|offset| !other_offsets.contains(offset)));
+        let bytes = buffer.into_inner();
+        for (offset, sampling_distance) in offsets.iter().zip(&settings.sampling_distances) {
+            // The sampling distance is the first field of the uniforms.
+            let offset = *offset as usize;
+            let value = f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            assert_eq!(value, *sampling_distance);
+        }
+    }
+}
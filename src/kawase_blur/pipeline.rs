@@ -1,20 +1,22 @@
-use super::settings::{KawaseBlurSettings, KawaseBlurUniforms};
-use super::KAWASE_BLUR_SHADER_HANDLE;
+use super::settings::KawaseBlurUniforms;
+use super::{KawaseBlurUniformBuffer, KawaseBlurUniformOffsets, KAWASE_BLUR_SHADER_HANDLE};
+use crate::bind_groups::ViewBindGroups;
 use bevy::render::render_graph::RenderLabel;
 use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
-use bevy::render::render_resource::{BindGroupLayoutEntries, FilterMode, UniformBuffer};
-use bevy::render::renderer::RenderQueue;
+use bevy::render::render_resource::{BindGroupLayoutEntries, FilterMode};
 use bevy::{
     core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
     ecs::query::QueryItem,
     prelude::*,
     render::{
+        camera::ExtractedCamera,
         render_graph::{NodeRunError, RenderGraphContext, ViewNode},
         render_resource::{
-            BindGroupEntries, BindGroupLayout, CachedRenderPipelineId, FragmentState,
-            MultisampleState, Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
-            RenderPassDescriptor, RenderPipelineDescriptor, Sampler, SamplerBindingType,
-            SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+            BindGroup, BindGroupEntries, BindGroupLayout, BindingResource, CachedRenderPipelineId,
+            FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
+            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
+            SamplerBindingType, SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+            TextureView,
         },
         renderer::{RenderContext, RenderDevice},
         texture::BevyDefault,
@@ -30,20 +32,26 @@ pub(crate) struct KawaseBlurNode;
 
 // The ViewNode trait is required by the ViewNodeRunner
 impl ViewNode for KawaseBlurNode {
-    type ViewQuery = (&'static ViewTarget, &'static KawaseBlurSettings);
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ExtractedCamera,
+        &'static KawaseBlurUniformOffsets,
+    );
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, kawase_blur_settings): QueryItem<Self::ViewQuery>,
+        (view_target, camera, uniform_offsets): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let kawase_blur_pipeline = world.resource::<KawaseBlurPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
-        let queue = world.resource::<RenderQueue>();
+        let bind_groups = world.resource::<ViewBindGroups<KawaseBlurPipeline>>();
 
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(kawase_blur_pipeline.pipeline_id)
-        else {
+        let (Some(pipeline), Some(bind_groups)) = (
+            pipeline_cache.get_render_pipeline(kawase_blur_pipeline.pipeline_id),
+            bind_groups.get(graph.view_entity()),
+        ) else {
             return Ok(());
         };
 
@@ -51,28 +59,11 @@ impl ViewNode for KawaseBlurNode {
             .command_encoder()
             .push_debug_group("kawase_blur");
 
-        let mut uniform = UniformBuffer::<KawaseBlurUniforms>::default();
-        for &sampling_distance in kawase_blur_settings.sampling_distances.iter() {
-            uniform.set(KawaseBlurUniforms {
-                sampling_distance: sampling_distance as f32,
-                ..default()
-            });
-            uniform.write_buffer(render_context.render_device(), queue);
+        // Each pass binds the uniforms prepared for its own sampling distance.
+        for &uniform_offset in uniform_offsets.0.iter() {
             let post_process = view_target.post_process_write();
 
-            let bind_group = render_context.render_device().create_bind_group(
-                "kawase_blur_bind_group",
-                &kawase_blur_pipeline.layout,
-                // It's important for this to match the BindGroupLayout defined in the KawaseBlurPipeline
-                &BindGroupEntries::sequential((
-                    // Make sure to use the source view
-                    post_process.source,
-                    // Use the sampler created for the pipeline
-                    &kawase_blur_pipeline.sampler,
-                    // Set the settings binding
-                    uniform.binding().unwrap().clone(),
-                )),
-            );
+            let bind_group = bind_groups.get(post_process.source);
 
             let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
                 label: Some("kawase_blur_pass"),
@@ -88,8 +79,12 @@ impl ViewNode for KawaseBlurNode {
                 occlusion_query_set: None,
             });
 
+            // The other cameras sharing the render target keep their viewports.
+            if let Some(viewport) = &camera.viewport {
+                render_pass.set_camera_viewport(viewport);
+            }
             render_pass.set_render_pipeline(pipeline);
-            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_bind_group(0, bind_group, &[uniform_offset]);
             render_pass.draw(0..3, 0..1);
         }
 
@@ -117,7 +112,7 @@ impl FromWorld for KawaseBlurPipeline {
                 (
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
-                    uniform_buffer::<KawaseBlurUniforms>(false),
+                    uniform_buffer::<KawaseBlurUniforms>(true),
                 ),
             ),
         );
@@ -161,3 +156,60 @@ impl FromWorld for KawaseBlurPipeline {
         }
     }
 }
+
+impl KawaseBlurPipeline {
+    // Creates the bind group of a pass sampling `source`.
+    fn create_bind_group(
+        &self,
+        render_device: &RenderDevice,
+        source: &TextureView,
+        settings_binding: BindingResource,
+    ) -> BindGroup {
+        render_device.create_bind_group(
+            "kawase_blur_bind_group",
+            &self.layout,
+            // It's important for this to match the BindGroupLayout defined in the KawaseBlurPipeline
+            &BindGroupEntries::sequential((
+                // Make sure to use the source view
+                source,
+                // Use the sampler created for the pipeline
+                &self.sampler,
+                // Set the settings binding
+                settings_binding,
+            )),
+        )
+    }
+}
+
+// Each pass samples one of the main textures of the view, a bind group is prepared for both of them.
+pub(crate) fn prepare_kawase_blur_bind_groups(
+    render_device: Res<RenderDevice>,
+    kawase_blur_pipeline: Res<KawaseBlurPipeline>,
+    uniform_buffer: Res<KawaseBlurUniformBuffer>,
+    mut bind_groups: ResMut<ViewBindGroups<KawaseBlurPipeline>>,
+    views: Query<(Entity, &ViewTarget), With<KawaseBlurUniformOffsets>>,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(settings_binding)) =
+        (uniform_buffer.0.buffer(), uniform_buffer.0.binding())
+    else {
+        return;
+    };
+    for (view, view_target) in &views {
+        bind_groups.update(
+            view,
+            &[
+                view_target.main_texture_view(),
+                view_target.main_texture_other_view(),
+            ],
+            Some(buffer.id()),
+            |source| {
+                kawase_blur_pipeline.create_bind_group(
+                    &render_device,
+                    source,
+                    settings_binding.clone(),
+                )
+            },
+        );
+    }
+}
@@ -1,28 +1,41 @@
-use bevy::ecs::query::QueryItem;
 use bevy::prelude::*;
-use bevy::render::{extract_component::ExtractComponent, render_resource::ShaderType};
+use bevy::render::render_resource::ShaderType;
+
+use crate::{extract::ExtractBlurSettings, BlurAlphaMode, BlurEdgeMode, BlurQuality, BlurSetting};
 
 /// Applies a kawase blur effect to a 2d or 3d camera if the [`KawaseBlurPlugin`] is active.
 ///
-#[derive(Component, Reflect, Clone, Debug)]
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
 #[reflect(Component, Default)]
 pub struct KawaseBlurSettings {
     /// Sampling distances for each consecutive filter pass.
     /// For a value of `d` the pixel value for this pass will be the mean value return for the pixel
     /// sampling done at the four corners `(d+0.5,d+0.5)`, `(d-0.5,d+0,5)`, `(d-0.5,d-0.5)` and `(d+0.5,d-0.5)`
-    /// - Each value will be clamped to the range [0..9]
-    pub sampling_distances: Vec<u32>,
+    ///
+    /// Fractional values are supported, the bilinear sampling blending the neighbouring pixels accordingly.
+    /// - Each value will be clamped to the range [0.0..9.0]
+    pub sampling_distances: Vec<f32>,
+    /// Defines how the pixels beyond the edges of the view are sampled.
+    /// - Defaults to [`BlurEdgeMode::Clamp`]
+    pub edge_mode: BlurEdgeMode,
+    /// Defines how the alpha channel is blurred, for cameras rendering to a transparent target.
+    /// - Defaults to [`BlurAlphaMode::Straight`]
+    pub alpha_mode: BlurAlphaMode,
 }
 impl Default for KawaseBlurSettings {
     fn default() -> Self {
         Self {
-            sampling_distances: vec![0, 1, 2, 2, 3],
+            sampling_distances: vec![0.0, 1.0, 2.0, 2.0, 3.0],
+            edge_mode: BlurEdgeMode::Clamp,
+            alpha_mode: BlurAlphaMode::Straight,
         }
     }
 }
 impl crate::BlurSetting for KawaseBlurSettings {
     const NO_BLUR: KawaseBlurSettings = KawaseBlurSettings {
         sampling_distances: vec![],
+        edge_mode: BlurEdgeMode::Clamp,
+        alpha_mode: BlurAlphaMode::Straight,
     };
 
     fn sampling_per_pixel(&self) -> f32 {
@@ -32,30 +45,205 @@ impl crate::BlurSetting for KawaseBlurSettings {
     fn passes(&self) -> u32 {
         self.sampling_distances.len() as u32
     }
+
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64 {
+        let pixels = viewport_size.x as f64 * viewport_size.y as f64;
+        (pixels * self.sampling_per_pixel() as f64).round() as u64
+    }
+
+    fn equivalent_sigma(&self) -> f32 {
+        self.create_concrete()
+            .sampling_distances
+            .iter()
+            .map(|&d| kawase_pass_variance(d))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    /// The number of passes is interpolated, and each sampling distance is interpolated with the one
+    /// at the same index in `other`. When missing, a sampling distance of 0 is used instead.
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let (start, end) = (self.create_concrete(), other.create_concrete());
+        let passes = crate::lerp_u32(
+            start.sampling_distances.len() as u32,
+            end.sampling_distances.len() as u32,
+            t,
+        );
+        let sampling_distances = (0..passes as usize)
+            .map(|i| {
+                let d1 = start.sampling_distances.get(i).copied().unwrap_or(0.0);
+                let d2 = end.sampling_distances.get(i).copied().unwrap_or(0.0);
+                d1 + (d2 - d1) * t
+            })
+            .collect();
+        KawaseBlurSettings {
+            sampling_distances,
+            edge_mode: if t < 0.5 {
+                self.edge_mode
+            } else {
+                other.edge_mode
+            },
+            alpha_mode: if t < 0.5 {
+                self.alpha_mode
+            } else {
+                other.alpha_mode
+            },
+        }
+        .create_concrete()
+    }
 }
 impl KawaseBlurSettings {
+    // Highest number of passes of the settings computed by `from_sigma`.
+    pub(crate) const MAX_PASSES: u32 = 16;
+
+    /// Computes the `KawaseBlurSettings` whose blur is the closest to a gaussian blur of the given `sigma`.
+    ///
+    /// The sampling distances are increasing integers, starting from 0, as long as they fit in the gaussian variance,
+    /// a last pass with a fractional sampling distance being added to match the remaining variance when possible.
+    ///
+    /// The settings have at most 16 passes, the sampling distances from 0 to 9 followed by six passes of 9,
+    /// so the largest reachable equivalent sigma is about 29.6. Larger values of `sigma` give these settings.
+    ///
+    /// ```
+    ///# use bevy_camera_blur::*;
+    /// let settings = KawaseBlurSettings::from_sigma(f32::INFINITY);
+    /// assert_eq!(settings.sampling_distances.len(), 16);
+    /// assert!((settings.equivalent_sigma() - 29.6).abs() < 0.1);
+    /// ```
+    pub fn from_sigma(sigma: f32) -> KawaseBlurSettings {
+        KawaseBlurSettings::from_sigma_with_step(sigma, 1.0)
+    }
+    // Computes the settings as `from_sigma` does, the sampling distances increasing by `step`.
+    fn from_sigma_with_step(sigma: f32, step: f32) -> KawaseBlurSettings {
+        let max_passes = Self::MAX_PASSES as usize;
+        // Beyond the variance of the passes all using the highest sampling distance, the passes are capped anyway.
+        let max_variance = max_passes as f32 * kawase_pass_variance(9.0);
+        let mut remaining_variance = sigma.max(0.0).powi(2).min(max_variance);
+        let mut sampling_distances = vec![];
+        let mut next_distance = 0.0;
+        while sampling_distances.len() < max_passes
+            && kawase_pass_variance(next_distance) <= remaining_variance
+        {
+            sampling_distances.push(next_distance);
+            remaining_variance -= kawase_pass_variance(next_distance);
+            next_distance = (next_distance + step).min(9.0);
+        }
+        if sampling_distances.len() < max_passes
+            && remaining_variance > kawase_pass_variance(0.0) / 2.0
+        {
+            // The pass variance increases with the sampling distance,
+            // the last distance is found by bisection in `[0.0, next_distance]`.
+            let (mut low, mut high) = (0.0, next_distance);
+            for _ in 0..20 {
+                let middle = (low + high) / 2.0;
+                if kawase_pass_variance(middle) < remaining_variance {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            sampling_distances.push((low + high) / 2.0);
+            sampling_distances.sort_by(f32::total_cmp);
+        }
+        KawaseBlurSettings {
+            sampling_distances,
+            ..default()
+        }
+    }
+    /// Computes the settings used for a [`BlurQuality`] by the default [`BlurQualityMapping`](crate::BlurQualityMapping),
+    /// with about the same equivalent sigma.
+    ///
+    /// The settings are computed as [`KawaseBlurSettings::from_sigma`] does, the sampling distances
+    /// increasing by 3 for [`BlurQuality::Low`], 2 for [`BlurQuality::Medium`] and 0.5 for [`BlurQuality::Ultra`],
+    /// and only used when they have no more passes, or more for [`BlurQuality::Ultra`].
+    /// [`BlurQuality::High`] keeps the settings.
+    pub fn with_quality(&self, quality: BlurQuality) -> KawaseBlurSettings {
+        let step = match quality {
+            BlurQuality::Low => 3.0,
+            BlurQuality::Medium => 2.0,
+            BlurQuality::High => return self.clone(),
+            BlurQuality::Ultra => 0.5,
+        };
+        let settings = KawaseBlurSettings {
+            edge_mode: self.edge_mode,
+            alpha_mode: self.alpha_mode,
+            ..KawaseBlurSettings::from_sigma_with_step(self.equivalent_sigma(), step)
+        };
+        let passes = (settings.passes(), self.passes());
+        if (quality == BlurQuality::Ultra) == (passes.0 > passes.1) {
+            settings
+        } else {
+            self.clone()
+        }
+    }
     /// Computes a new `KawaseBlurSettings` where each attribute is legal as expected by the shader.
     pub fn create_concrete(&self) -> KawaseBlurSettings {
         let sampling_distances = self
             .sampling_distances
             .iter()
-            .map(|&v| v.clamp(0, 9))
+            .map(|&v| v.clamp(0.0, 9.0))
             .collect::<Vec<_>>();
-        KawaseBlurSettings { sampling_distances }
+        KawaseBlurSettings {
+            sampling_distances,
+            edge_mode: self.edge_mode,
+            alpha_mode: self.alpha_mode,
+        }
     }
 }
 
-impl ExtractComponent for KawaseBlurSettings {
-    type QueryData = &'static Self;
+// Variance, in pixels, of one pass of the Kawase blur for a sampling distance `d`.
+// Each axis averages the bilinear samples done at `d+0.5` and `-(d+0.5)`,
+// blending the pixels at the distances surrounding `d+0.5`.
+// For an integer `d`, the pixels at distances `d` and `d+1` each get a quarter of the weight.
+pub(crate) fn kawase_pass_variance(d: f32) -> f32 {
+    let offset = d + 0.5;
+    let (base, t) = (offset.floor(), offset.fract());
+    base * base * (1.0 - t) + (base + 1.0) * (base + 1.0) * t
+}
 
-    type QueryFilter = ();
+impl ExtractBlurSettings for KawaseBlurSettings {
     type Out = KawaseBlurSettings;
 
-    fn extract_component(settings: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
-        if settings.sampling_distances.is_empty() {
+    // The offsets of the samples, `d+0.5`, are scaled. Beyond the highest sampling distance,
+    // the passes are replaced by the ones of `from_sigma` for the same equivalent sigma, up to its largest one.
+    fn scaled(&self, factor: f32) -> Self {
+        let sampling_distances: Vec<f32> = self
+            .create_concrete()
+            .sampling_distances
+            .iter()
+            .map(|&d| ((d + 0.5) * factor - 0.5).max(0.0))
+            .collect();
+        if sampling_distances.iter().all(|&d| d <= 9.0) {
+            KawaseBlurSettings {
+                sampling_distances,
+                ..self.clone()
+            }
+        } else {
+            let variance: f32 = sampling_distances
+                .iter()
+                .map(|&d| kawase_pass_variance(d))
+                .sum();
+            KawaseBlurSettings {
+                edge_mode: self.edge_mode,
+                alpha_mode: self.alpha_mode,
+                ..KawaseBlurSettings::from_sigma(variance.sqrt())
+            }
+        }
+    }
+
+    fn map_quality(&self, quality: BlurQuality) -> Self {
+        self.with_quality(quality)
+    }
+
+    fn capped(&self, max_sigma: f32) -> Self {
+        crate::extract::capped_to_sigma(self, max_sigma)
+    }
+
+    fn extract(&self, _camera: &Camera) -> Option<Self::Out> {
+        if self.sampling_distances.is_empty() {
             None
         } else {
-            Some(settings.create_concrete())
+            Some(self.create_concrete())
         }
     }
 }
@@ -65,5 +253,11 @@ impl ExtractComponent for KawaseBlurSettings {
 pub struct KawaseBlurUniforms {
     // Sampling distance for the current pass.
     pub sampling_distance: f32,
-    pub _webgl2_padding: Vec3,
+    // Value of the legalized edge_mode
+    pub edge_mode: u32,
+    // Value of the alpha_mode
+    pub alpha_mode: u32,
+    pub _webgl2_padding: f32,
+    // UV rectangle of the viewport of the camera in the view target
+    pub viewport: Vec4,
 }
@@ -0,0 +1,51 @@
+#![cfg(feature = "bevy_tweening")]
+use bevy_tweening::Lens;
+
+use crate::{BlurSetting, KawaseBlurSettings};
+
+/// A `bevy_tweening` Lens implementation to allow animation of the Kawase blur.
+///
+/// This will tweens each of the `sampling_distances`
+/// of the [`KawaseBlurSettings`], see [`BlurSetting::interpolate`].
+/// # Example
+///
+/// System that will create an animation from a no blur to the default blur effect
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_tweening::*;
+///# use bevy_camera_blur::*;
+///# use std::time::Duration;
+///
+///fn blur(
+///    mut commands: Commands,
+///    camera: Query<Entity, (With<Camera>, With<KawaseBlurSettings>)>,
+///) {
+///    let tween = Tween::new(
+///        EaseFunction::QuadraticInOut,
+///        Duration::from_millis(500),
+///        KawaseBlurLens::new(
+///             KawaseBlurSettings::NO_BLUR,
+///             KawaseBlurSettings::default(),
+///        )
+///    );
+///    let camera_entity = camera.single();
+///    commands.entity(camera_entity).insert(Animator::new(tween));
+///}
+/// ```
+pub struct KawaseBlurLens {
+    /// Kawase blur settings at the start of the tweening
+    start: KawaseBlurSettings,
+    /// Kawase blur settings at the end of the tweening
+    end: KawaseBlurSettings,
+}
+impl crate::BlurSettingLens<KawaseBlurSettings> for KawaseBlurLens {
+    fn new(start: KawaseBlurSettings, end: KawaseBlurSettings) -> Self {
+        KawaseBlurLens { start, end }
+    }
+}
+impl Lens<KawaseBlurSettings> for KawaseBlurLens {
+    fn lerp(&mut self, target: &mut KawaseBlurSettings, ratio: f32) {
+        *target = self.start.interpolate(&self.end, ratio);
+    }
+}
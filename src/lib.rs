@@ -14,9 +14,40 @@
 //! | Box Blur | [`BoxBlurPlugin`] |
 //! | Kawase Blur | [`KawaseBlurPlugin`] |
 //! | Dual Blur | [`DualBlurPlugin`] |
+//! | Variable Blur | [`VariableBlurPlugin`] |
+//!
+//! The [`BlurPlanner`] can select the cheapest algorithm and settings for a targeted amount of blur,
+//! and [`BlurAnalysis`] can compare the quality of each settings against a true gaussian blur.
+//!
+//! The [`BlurAutoQualityPlugin`] switches a camera to cheaper settings looking as blurry
+//! when the frame time exceeds the [`BlurAutoQuality`] budget.
+//!
+//! # Animation
+//!
+//! Any settings can be animated using a [`BlurTransition`] component added to the Camera entity.
+//!
+//! # Units
+//!
+//! The settings are in physical pixels by default.
+//! A [`BlurUnits`] component added to the Camera entity keeps the blur the same size
+//! across screen resolutions, window scale factors or zoom levels.
+//!
+//! # Quality
+//!
+//! A [`BlurQuality`] resource trades the accuracy of all the blurs for their cost,
+//! each algorithm mapping the settings of the cameras to the quality with a [`BlurQualityMapping`].
+//!
+//! # Accessibility
+//!
+//! A [`BlurAccessibility`] resource caps the strength of all the blurs, disables them,
+//! or disables their animation, whatever the settings of the cameras.
 //!
 //! # Features flags
 //!
+//! * **`bevy_animation`** -
+//!   When enabled Bevy's `Animatable` trait is implemented for each settings,
+//!   so that they can be interpolated and blended by Bevy's animation system.
+//!
 //! * **`bevy_tweening`** -
 //!   When enabled `Lens` implementations are provided for each effect to use with the `bevy_tweening` crate.
 //!   This adds a dependency on the `bevy_tweening` crate.
@@ -30,6 +61,33 @@ mod kawase_blur;
 pub use kawase_blur::*;
 mod dual_blur;
 pub use dual_blur::*;
+mod variable_blur;
+pub use variable_blur::*;
+mod planner;
+pub use planner::*;
+mod analysis;
+pub use analysis::*;
+mod transition;
+pub use transition::*;
+mod auto_quality;
+pub use auto_quality::*;
+mod edge_mode;
+pub use edge_mode::*;
+mod alpha_mode;
+pub use alpha_mode::*;
+mod units;
+pub use units::*;
+mod quality;
+pub use quality::*;
+mod accessibility;
+pub use accessibility::*;
+mod animatable;
+mod bind_groups;
+mod compute;
+mod extract;
+mod viewport;
+
+use bevy::math::UVec2;
 
 /// Provides a const settings
 pub trait BlurSetting {
@@ -50,6 +108,56 @@ pub trait BlurSetting {
     /// This should only be used during dev to check
     /// the quality/cost compromise.
     fn passes(&self) -> u32;
+    /// Provides for this setting the total number of texture sampling done
+    /// to blur a viewport of `viewport_size` physical pixels,
+    /// taking into account the resolution each pass is done at.
+    ///
+    /// This is the cost model used by the [`BlurPlanner`].
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64;
+    /// Provides for this setting an estimation of the standard deviation, in pixels,
+    /// of the gaussian blur that would look equivalent.
+    ///
+    /// This can be used to compare settings of different algorithms,
+    /// for example to swap algorithms without changing how blurry the scene looks.
+    /// Each settings type provides a `from_sigma` constructor doing the reverse conversion.
+    ///
+    /// ```
+    ///# use bevy_camera_blur::*;
+    /// let gaussian = GaussianBlurSettings::default();
+    /// let kawase = KawaseBlurSettings::from_sigma(gaussian.equivalent_sigma());
+    /// assert!((kawase.equivalent_sigma() - gaussian.equivalent_sigma()).abs() < 0.5);
+    /// ```
+    fn equivalent_sigma(&self) -> f32;
+    /// Computes a setting between `self` and `other`, `t` being the interpolation factor
+    /// with `0.0` resulting in `self` and `1.0` in `other`.
+    ///
+    /// The result is always legal as expected by the shader.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+// Linearly interpolates between two integer attributes.
+fn lerp_u32(a: u32, b: u32, t: f32) -> u32 {
+    (a as f32 + (b as f32 - a as f32) * t).round().max(0.0) as u32
+}
+
+// Linearly interpolates between two odd kernel sizes, keeping the result odd.
+fn lerp_kernel_size(a: u32, b: u32, t: f32) -> u32 {
+    2 * lerp_u32(a / 2, b / 2, t) + 1
+}
+
+// Scales the radius of an odd kernel size, keeping the result odd.
+fn scale_kernel_size(kernel_size: u32, scale: f32) -> u32 {
+    2 * ((kernel_size / 2) as f32 * scale).round() as u32 + 1
+}
+
+// Rounds up the loop count of a shader specialized on it, to limit the number of pipeline variants:
+// exact up to 8, then by steps of 4 up to 32, then by steps of 16.
+fn quantize_loop_count(count: u32) -> u32 {
+    match count {
+        0..=8 => count,
+        9..=32 => count.next_multiple_of(4),
+        _ => count.next_multiple_of(16),
+    }
 }
 
 /// Can create a Lens from a `start` and an `end` setting
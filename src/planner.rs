@@ -0,0 +1,329 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::{
+    extract::ExtractBlurSettings, BlurAlphaMode, BlurEdgeMode, BlurSetting, BoxBlurSettings,
+    DualBlurSettings, GaussianBlurSettings, KawaseBlurSettings,
+};
+
+/// Selects the cheapest blur algorithm and settings that produce a targeted amount of blur.
+///
+/// The amount of blur is expressed as the `sigma` of the equivalent gaussian blur,
+/// see [`BlurSetting::equivalent_sigma`], and the cost as the number of texture sampling
+/// needed for a frame, see [`BlurSetting::texture_samplings`].
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///fn blur_camera(mut commands: Commands, camera: Query<(Entity, &Camera)>) {
+///    let (entity, camera) = camera.single();
+///    let Some(viewport_size) = camera.physical_viewport_size() else {
+///        return;
+///    };
+///    // Allows an average of 16 texture samplings per pixel.
+///    let budget = 16 * viewport_size.x as u64 * viewport_size.y as u64;
+///    if let Some(plan) = BlurPlanner::new(8.0, viewport_size, budget).plan() {
+///        plan.insert(&mut commands.entity(entity));
+///    }
+///}
+/// ```
+#[derive(Clone, Debug)]
+pub struct BlurPlanner {
+    /// Standard deviation, in pixels, of the targeted gaussian blur.
+    pub sigma: f32,
+    /// Physical size, in pixels, of the viewport of the blurred camera.
+    pub viewport_size: UVec2,
+    /// Maximum number of texture sampling that can be done each frame for the blur.
+    pub texture_sampling_budget: u64,
+    /// Maximum relative difference between `sigma` and the equivalent sigma of a planned setting.
+    /// - Defaults to 0.1
+    pub tolerance: f32,
+}
+
+impl BlurPlanner {
+    /// Creates a new planner for a targeted `sigma`, with a default tolerance.
+    pub fn new(sigma: f32, viewport_size: UVec2, texture_sampling_budget: u64) -> Self {
+        Self {
+            sigma,
+            viewport_size,
+            texture_sampling_budget,
+            tolerance: 0.1,
+        }
+    }
+
+    /// Sets the maximum relative difference between the targeted `sigma`
+    /// and the equivalent sigma of a planned setting.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Provides all the settings considered by the planner for the targeted `sigma`,
+    /// whatever their cost and quality.
+    pub fn candidates(&self) -> Vec<BlurPlan> {
+        let mut candidates = vec![
+            BlurPlan::Gaussian(GaussianBlurSettings::from_sigma(self.sigma)),
+            BlurPlan::Kawase(KawaseBlurSettings::from_sigma(self.sigma)),
+            BlurPlan::Dual(DualBlurSettings::from_sigma(self.sigma)),
+        ];
+        candidates.extend((2..=5).map(|passes| {
+            BlurPlan::Box(BoxBlurSettings::from_sigma_with_passes(self.sigma, passes))
+        }));
+        candidates
+    }
+
+    /// Provides the cheapest setting whose equivalent sigma is within the tolerance,
+    /// and whose cost is within the budget.
+    ///
+    /// A `sigma` of 0 or less gives [`BlurPlan::NO_BLUR`].
+    /// Returns `None` when no setting fulfills both conditions.
+    pub fn plan(&self) -> Option<BlurPlan> {
+        self.plan_among(|_| true)
+    }
+
+    // Plans as `plan` does, only considering the candidates for which `is_available` is true,
+    // a `sigma` of 0 or less giving the settings of no blur of the first available algorithm.
+    pub(crate) fn plan_among(&self, is_available: impl Fn(&BlurPlan) -> bool) -> Option<BlurPlan> {
+        if self.sigma <= 0.0 {
+            return [
+                BlurPlan::NO_BLUR,
+                BlurPlan::Box(BoxBlurSettings::NO_BLUR),
+                BlurPlan::Kawase(KawaseBlurSettings::NO_BLUR),
+                BlurPlan::Dual(DualBlurSettings::NO_BLUR),
+            ]
+            .into_iter()
+            .find(is_available);
+        }
+        self.candidates()
+            .into_iter()
+            .filter(is_available)
+            .filter(|plan| {
+                (plan.equivalent_sigma() - self.sigma).abs() <= self.tolerance * self.sigma
+            })
+            .map(|plan| (plan.texture_samplings(self.viewport_size), plan))
+            .filter(|(cost, _)| *cost <= self.texture_sampling_budget)
+            .min_by_key(|(cost, _)| *cost)
+            .map(|(_, plan)| plan)
+    }
+}
+
+/// A blur algorithm and its settings, as selected by the [`BlurPlanner`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlurPlan {
+    /// Blur using the [`GaussianBlurPlugin`](crate::GaussianBlurPlugin)
+    Gaussian(GaussianBlurSettings),
+    /// Blur using the [`BoxBlurPlugin`](crate::BoxBlurPlugin)
+    Box(BoxBlurSettings),
+    /// Blur using the [`KawaseBlurPlugin`](crate::KawaseBlurPlugin)
+    Kawase(KawaseBlurSettings),
+    /// Blur using the [`DualBlurPlugin`](crate::DualBlurPlugin)
+    Dual(DualBlurSettings),
+}
+
+impl BlurPlan {
+    /// Inserts the planned settings component on a camera entity,
+    /// removing the settings of all the other algorithms.
+    ///
+    /// The plugin associated with the planned algorithm must have been added to the app.
+    pub fn insert(self, entity: &mut EntityCommands) {
+        entity.remove::<(
+            GaussianBlurSettings,
+            BoxBlurSettings,
+            KawaseBlurSettings,
+            DualBlurSettings,
+        )>();
+        match self {
+            BlurPlan::Gaussian(settings) => entity.insert(settings),
+            BlurPlan::Box(settings) => entity.insert(settings),
+            BlurPlan::Kawase(settings) => entity.insert(settings),
+            BlurPlan::Dual(settings) => entity.insert(settings),
+        };
+    }
+
+    // Computes the plan whose blur is `factor` times wider.
+    pub(crate) fn scaled(&self, factor: f32) -> BlurPlan {
+        match self {
+            BlurPlan::Gaussian(settings) => BlurPlan::Gaussian(settings.scaled(factor)),
+            BlurPlan::Box(settings) => BlurPlan::Box(settings.scaled(factor)),
+            BlurPlan::Kawase(settings) => BlurPlan::Kawase(settings.scaled(factor)),
+            BlurPlan::Dual(settings) => BlurPlan::Dual(settings.scaled(factor)),
+        }
+    }
+
+    // Edge and alpha modes of the planned settings.
+    pub(crate) fn modes(&self) -> (BlurEdgeMode, BlurAlphaMode) {
+        match self {
+            BlurPlan::Gaussian(settings) => (settings.edge_mode, settings.alpha_mode),
+            BlurPlan::Box(settings) => (settings.edge_mode, settings.alpha_mode),
+            BlurPlan::Kawase(settings) => (settings.edge_mode, settings.alpha_mode),
+            BlurPlan::Dual(settings) => (settings.edge_mode, settings.alpha_mode),
+        }
+    }
+
+    // Sets the edge and alpha modes of the planned settings.
+    pub(crate) fn with_modes(
+        mut self,
+        (edge_mode, alpha_mode): (BlurEdgeMode, BlurAlphaMode),
+    ) -> BlurPlan {
+        match &mut self {
+            BlurPlan::Gaussian(settings) => {
+                (settings.edge_mode, settings.alpha_mode) = (edge_mode, alpha_mode)
+            }
+            BlurPlan::Box(settings) => {
+                (settings.edge_mode, settings.alpha_mode) = (edge_mode, alpha_mode)
+            }
+            BlurPlan::Kawase(settings) => {
+                (settings.edge_mode, settings.alpha_mode) = (edge_mode, alpha_mode)
+            }
+            BlurPlan::Dual(settings) => {
+                (settings.edge_mode, settings.alpha_mode) = (edge_mode, alpha_mode)
+            }
+        }
+        self
+    }
+}
+
+impl BlurSetting for BlurPlan {
+    const NO_BLUR: Self = BlurPlan::Gaussian(GaussianBlurSettings::NO_BLUR);
+
+    fn sampling_per_pixel(&self) -> f32 {
+        match self {
+            BlurPlan::Gaussian(settings) => settings.sampling_per_pixel(),
+            BlurPlan::Box(settings) => settings.sampling_per_pixel(),
+            BlurPlan::Kawase(settings) => settings.sampling_per_pixel(),
+            BlurPlan::Dual(settings) => settings.sampling_per_pixel(),
+        }
+    }
+
+    fn passes(&self) -> u32 {
+        match self {
+            BlurPlan::Gaussian(settings) => settings.passes(),
+            BlurPlan::Box(settings) => settings.passes(),
+            BlurPlan::Kawase(settings) => settings.passes(),
+            BlurPlan::Dual(settings) => settings.passes(),
+        }
+    }
+
+    fn texture_samplings(&self, viewport_size: UVec2) -> u64 {
+        match self {
+            BlurPlan::Gaussian(settings) => settings.texture_samplings(viewport_size),
+            BlurPlan::Box(settings) => settings.texture_samplings(viewport_size),
+            BlurPlan::Kawase(settings) => settings.texture_samplings(viewport_size),
+            BlurPlan::Dual(settings) => settings.texture_samplings(viewport_size),
+        }
+    }
+
+    fn equivalent_sigma(&self) -> f32 {
+        match self {
+            BlurPlan::Gaussian(settings) => settings.equivalent_sigma(),
+            BlurPlan::Box(settings) => settings.equivalent_sigma(),
+            BlurPlan::Kawase(settings) => settings.equivalent_sigma(),
+            BlurPlan::Dual(settings) => settings.equivalent_sigma(),
+        }
+    }
+
+    /// Plans of different algorithms can't be interpolated,
+    /// `self` is returned until `t` reaches `0.5`, `other` after.
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (BlurPlan::Gaussian(s1), BlurPlan::Gaussian(s2)) => {
+                BlurPlan::Gaussian(s1.interpolate(s2, t))
+            }
+            (BlurPlan::Box(s1), BlurPlan::Box(s2)) => BlurPlan::Box(s1.interpolate(s2, t)),
+            (BlurPlan::Kawase(s1), BlurPlan::Kawase(s2)) => BlurPlan::Kawase(s1.interpolate(s2, t)),
+            (BlurPlan::Dual(s1), BlurPlan::Dual(s2)) => BlurPlan::Dual(s1.interpolate(s2, t)),
+            _ if t < 0.5 => self.clone(),
+            _ => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT_SIZE: UVec2 = UVec2::new(1920, 1080);
+
+    fn within_tolerance(planner: &BlurPlanner, plan: &BlurPlan) -> bool {
+        (plan.equivalent_sigma() - planner.sigma).abs() <= planner.tolerance * planner.sigma
+    }
+
+    #[test]
+    fn plans_the_cheapest_candidate() {
+        for sigma in [1.0, 4.0, 12.0, 40.0] {
+            let planner = BlurPlanner::new(sigma, VIEWPORT_SIZE, u64::MAX);
+            let plan = planner.plan().unwrap();
+            let cost = plan.texture_samplings(VIEWPORT_SIZE);
+            assert!(within_tolerance(&planner, &plan), "{plan:?}");
+            for candidate in planner.candidates() {
+                if within_tolerance(&planner, &candidate) {
+                    assert!(cost <= candidate.texture_samplings(VIEWPORT_SIZE));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plans_within_the_tolerance() {
+        let planner = BlurPlanner::new(8.0, VIEWPORT_SIZE, u64::MAX);
+        let mut previous_cost = u64::MAX;
+        // A wider tolerance never gives a more expensive plan.
+        for tolerance in [0.001, 0.05, 0.1, 0.3] {
+            let planner = planner.clone().with_tolerance(tolerance);
+            let plan = planner.plan().unwrap();
+            assert!(within_tolerance(&planner, &plan), "{plan:?}");
+            let cost = plan.texture_samplings(VIEWPORT_SIZE);
+            assert!(cost <= previous_cost);
+            previous_cost = cost;
+        }
+    }
+
+    #[test]
+    fn plans_within_the_budget() {
+        let planner = BlurPlanner::new(8.0, VIEWPORT_SIZE, u64::MAX);
+        let cheapest = planner.plan().unwrap().texture_samplings(VIEWPORT_SIZE);
+        let budgeted = BlurPlanner {
+            texture_sampling_budget: cheapest,
+            ..planner.clone()
+        };
+        assert_eq!(budgeted.plan(), planner.plan());
+        let too_low = BlurPlanner {
+            texture_sampling_budget: cheapest - 1,
+            ..planner
+        };
+        assert_eq!(too_low.plan(), None);
+    }
+
+    #[test]
+    fn plans_among_the_available_algorithms() {
+        let planner = BlurPlanner::new(8.0, VIEWPORT_SIZE, u64::MAX);
+        let is_kawase = |plan: &BlurPlan| matches!(plan, BlurPlan::Kawase(_));
+        assert!(planner
+            .plan_among(is_kawase)
+            .is_some_and(|plan| is_kawase(&plan)));
+        assert_eq!(planner.plan_among(|_| false), None);
+
+        let no_blur = BlurPlanner::new(0.0, VIEWPORT_SIZE, 0);
+        assert_eq!(no_blur.plan(), Some(BlurPlan::NO_BLUR));
+        let is_dual = |plan: &BlurPlan| matches!(plan, BlurPlan::Dual(_));
+        assert_eq!(
+            no_blur.plan_among(is_dual),
+            Some(BlurPlan::Dual(DualBlurSettings::NO_BLUR))
+        );
+        assert_eq!(no_blur.plan_among(|_| false), None);
+    }
+
+    #[test]
+    fn texture_samplings_keep_fractional_samplings() {
+        // The running sum compute shaders sum the whole kernel once per line segment.
+        let settings = BoxBlurSettings {
+            kernel_size: 41,
+            ..BoxBlurSettings::default()
+        };
+        assert!(settings.sampling_per_pixel().fract() != 0.0);
+        let pixels = (VIEWPORT_SIZE.x * VIEWPORT_SIZE.y) as f64;
+        let expected = (pixels * settings.sampling_per_pixel() as f64).round() as u64;
+        assert_eq!(settings.texture_samplings(VIEWPORT_SIZE), expected);
+    }
+}
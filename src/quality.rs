@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+/// Global quality of the blurs, for example selected by the player in a settings menu.
+///
+/// The settings components of the cameras describe the intended blur, in [`BlurQuality::High`].
+/// When extracted, they are mapped to the settings used for the current quality by the
+/// [`BlurQualityMapping`] of their algorithm, trading accuracy for cost while keeping about
+/// the same equivalent sigma, see [`BlurSetting::equivalent_sigma`](crate::BlurSetting::equivalent_sigma).
+///
+/// The quality is [`BlurQuality::High`] when the resource is missing.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///fn lower_quality(mut commands: Commands) {
+///    commands.insert_resource(BlurQuality::Low);
+///}
+///```
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[reflect(Resource, Default)]
+pub enum BlurQuality {
+    /// The cheapest settings, with visible artefacts.
+    Low,
+    /// Cheaper settings, with some artefacts.
+    Medium,
+    /// The settings of the cameras, as set by the game.
+    #[default]
+    High,
+    /// More expensive settings, closer to a gaussian blur.
+    Ultra,
+}
+
+/// Maps the blur settings `T` of the cameras to the settings used for a [`BlurQuality`].
+///
+/// Each blur plugin adds the default mapping of its settings, see for example
+/// [`GaussianBlurSettings::with_quality`](crate::GaussianBlurSettings::with_quality),
+/// unless the resource is already present. It can be replaced to customize the cost of each quality.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///fn customize_dual_blur_quality(mut commands: Commands) {
+///    commands.insert_resource(BlurQualityMapping::new(
+///        |settings: &DualBlurSettings, quality| match quality {
+///            // The low quality skips the blur.
+///            BlurQuality::Low => DualBlurSettings::NO_BLUR,
+///            quality => settings.with_quality(quality),
+///        },
+///    ));
+///}
+///```
+#[derive(Resource, Clone, Copy)]
+pub struct BlurQualityMapping<T> {
+    /// Computes the settings used for a quality from the settings of a camera.
+    pub map: fn(&T, BlurQuality) -> T,
+}
+
+impl<T> BlurQualityMapping<T> {
+    /// Creates the mapping computing the settings used for a quality with `map`.
+    pub fn new(map: fn(&T, BlurQuality) -> T) -> Self {
+        Self { map }
+    }
+}
+
+// Adds the default mapping of the settings `T` to the app, unless already present, and the `BlurQuality` type.
+pub(crate) fn init_quality_mapping<T: Send + Sync + 'static>(
+    app: &mut App,
+    map: fn(&T, BlurQuality) -> T,
+) {
+    app.register_type::<BlurQuality>();
+    if !app.world.contains_resource::<BlurQualityMapping<T>>() {
+        app.insert_resource(BlurQualityMapping::new(map));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BlurSetting, BoxBlurSettings, DualBlurSettings, GaussianBlurSettings, KawaseBlurSettings,
+    };
+
+    fn assert_same_sigma<T: BlurSetting>(settings: &T, mapped: T) {
+        let (expected, sigma) = (settings.equivalent_sigma(), mapped.equivalent_sigma());
+        assert!(
+            (sigma / expected - 1.0).abs() < 0.1,
+            "{sigma} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn qualities_keep_the_equivalent_sigma() {
+        let qualities = [
+            BlurQuality::Low,
+            BlurQuality::Medium,
+            BlurQuality::High,
+            BlurQuality::Ultra,
+        ];
+        for quality in qualities {
+            let gaussian = GaussianBlurSettings::default();
+            assert_same_sigma(&gaussian, gaussian.with_quality(quality));
+            let box_blur = BoxBlurSettings::default();
+            assert_same_sigma(&box_blur, box_blur.with_quality(quality));
+            let kawase = KawaseBlurSettings::default();
+            assert_same_sigma(&kawase, kawase.with_quality(quality));
+            let dual = DualBlurSettings::default();
+            assert_same_sigma(&dual, dual.with_quality(quality));
+        }
+    }
+}
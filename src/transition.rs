@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{BlurAccessibility, BlurSetting};
+
+/// Animates the blur settings component `S` of a camera from a `from` setting to a `to` setting.
+///
+/// The settings component is inserted on the camera entity if missing,
+/// and updated each frame, using Bevy's own [`Time`].
+/// Once the transition is over, the `BlurTransition` component is removed
+/// and a [`BlurTransitionFinished`] event is sent.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///# use std::time::Duration;
+///
+///fn open_pause_menu(mut commands: Commands, camera: Query<Entity, With<Camera>>) {
+///    commands.entity(camera.single()).insert(
+///        BlurTransition::new(
+///            GaussianBlurSettings::NO_BLUR,
+///            GaussianBlurSettings::default(),
+///            Duration::from_millis(500),
+///        )
+///        .with_easing(BlurEasing::QuadraticInOut)
+///        // The game is paused, the blur must still be animated.
+///        .with_clock(BlurTransitionClock::Real),
+///    );
+///}
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct BlurTransition<S: BlurSetting> {
+    /// Settings at the start of the transition.
+    pub from: S,
+    /// Settings at the end of the transition.
+    pub to: S,
+    /// Duration of one run of the transition.
+    pub duration: Duration,
+    /// Easing applied to the transition progress.
+    /// - Defaults to [`BlurEasing::Linear`]
+    pub easing: BlurEasing,
+    /// Defines how many times the transition is run.
+    /// - Defaults to [`BlurTransitionRepeat::Never`]
+    pub repeat: BlurTransitionRepeat,
+    /// Defines the clock advancing the transition.
+    /// - Defaults to [`BlurTransitionClock::Virtual`]
+    pub clock: BlurTransitionClock,
+    /// When `true`, the settings component is removed from the camera
+    /// once the transition is over, if `to` does not create any blur.
+    /// - Defaults to `false`
+    pub remove_when_no_blur: bool,
+    elapsed: Duration,
+    runs: u32,
+}
+
+impl<S: BlurSetting> BlurTransition<S> {
+    /// Creates a transition from a `from` setting to a `to` setting in `duration`.
+    pub fn new(from: S, to: S, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing: BlurEasing::default(),
+            repeat: BlurTransitionRepeat::default(),
+            clock: BlurTransitionClock::default(),
+            remove_when_no_blur: false,
+            elapsed: Duration::ZERO,
+            runs: 0,
+        }
+    }
+
+    /// Sets the easing applied to the transition progress.
+    pub fn with_easing(mut self, easing: BlurEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets how many times the transition is run.
+    pub fn with_repeat(mut self, repeat: BlurTransitionRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Sets the clock advancing the transition.
+    pub fn with_clock(mut self, clock: BlurTransitionClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Removes the settings component from the camera at the end of the transition,
+    /// if `to` does not create any blur.
+    pub fn removing_when_no_blur(mut self) -> Self {
+        self.remove_when_no_blur = true;
+        self
+    }
+
+    /// Progress of the current run of the transition, between `0.0` and `1.0`, before easing.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        }
+    }
+
+    /// Returns `true` once the last run of the transition has been completed.
+    pub fn is_finished(&self) -> bool {
+        match self.repeat {
+            BlurTransitionRepeat::Never => self.runs >= 1,
+            BlurTransitionRepeat::Times(times) => self.runs >= times.max(1),
+            BlurTransitionRepeat::Forever => false,
+        }
+    }
+
+    /// Settings for the current progress of the transition.
+    pub fn current(&self) -> S {
+        self.from
+            .interpolate(&self.to, self.easing.ease(self.progress()))
+    }
+
+    // Jumps to the end of the last run of the transition, even when repeated forever.
+    fn skip_to_end(&mut self) {
+        self.elapsed = self.duration;
+        self.runs = match self.repeat {
+            BlurTransitionRepeat::Times(times) => times.max(1),
+            _ => 1,
+        };
+    }
+
+    // Advances the transition by `delta`, restarting runs as needed.
+    fn tick(&mut self, delta: Duration) {
+        self.elapsed += delta;
+        while self.elapsed >= self.duration && !self.is_finished() {
+            self.runs += 1;
+            if self.is_finished() || self.duration.is_zero() {
+                self.elapsed = self.duration;
+                break;
+            }
+            self.elapsed -= self.duration;
+        }
+    }
+}
+
+/// Easing functions that can be applied to a [`BlurTransition`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum BlurEasing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Starts slowly, quadratic
+    QuadraticIn,
+    /// Ends slowly, quadratic
+    QuadraticOut,
+    /// Starts and ends slowly, quadratic
+    QuadraticInOut,
+    /// Starts slowly, cubic
+    CubicIn,
+    /// Ends slowly, cubic
+    CubicOut,
+    /// Starts and ends slowly, cubic
+    CubicInOut,
+}
+
+impl BlurEasing {
+    /// Applies the easing function on a progress `t` in `[0.0, 1.0]`.
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            BlurEasing::Linear => t,
+            BlurEasing::QuadraticIn => t * t,
+            BlurEasing::QuadraticOut => 1.0 - (1.0 - t).powi(2),
+            BlurEasing::QuadraticInOut if t < 0.5 => 2.0 * t * t,
+            BlurEasing::QuadraticInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            BlurEasing::CubicIn => t.powi(3),
+            BlurEasing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            BlurEasing::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            BlurEasing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Defines how many times a [`BlurTransition`] is run.
+///
+/// Each new run restarts from the `from` setting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum BlurTransitionRepeat {
+    /// The transition is run once.
+    #[default]
+    Never,
+    /// The transition is run the given number of times.
+    Times(u32),
+    /// The transition is never finished.
+    Forever,
+}
+
+/// Defines which clock advances a [`BlurTransition`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum BlurTransitionClock {
+    /// Uses [`Time<Virtual>`], the transition is paused and scaled with the game.
+    #[default]
+    Virtual,
+    /// Uses [`Time<Real>`], the transition is still animated when the game is paused,
+    /// for example for the blur behind a pause menu.
+    Real,
+}
+
+/// Event sent when a [`BlurTransition`] is finished.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BlurTransitionFinished {
+    /// The camera entity the transition was attached to.
+    pub entity: Entity,
+}
+
+/// Updates the settings component `S` of each camera with a [`BlurTransition<S>`].
+///
+/// The transitions are finished at once when the [`BlurAccessibility`] disables the animated blurs.
+pub(crate) fn animate_blur_transitions<S: BlurSetting + Component + Clone>(
+    mut commands: Commands,
+    mut transitions: Query<(Entity, &mut BlurTransition<S>, Option<&mut S>)>,
+    virtual_time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    accessibility: Option<Res<BlurAccessibility>>,
+    mut finished_events: EventWriter<BlurTransitionFinished>,
+) {
+    let disable_animated =
+        accessibility.is_some_and(|accessibility| accessibility.disable_animated);
+    for (entity, mut transition, settings) in transitions.iter_mut() {
+        if disable_animated {
+            transition.skip_to_end();
+        } else {
+            let delta = match transition.clock {
+                BlurTransitionClock::Virtual => virtual_time.delta(),
+                BlurTransitionClock::Real => real_time.delta(),
+            };
+            transition.tick(delta);
+        }
+        let current = transition.current();
+        if disable_animated || transition.is_finished() {
+            commands.entity(entity).remove::<BlurTransition<S>>();
+            finished_events.send(BlurTransitionFinished { entity });
+            if transition.remove_when_no_blur && transition.to.equivalent_sigma() == 0.0 {
+                commands.entity(entity).remove::<S>();
+                continue;
+            }
+        }
+        match settings {
+            Some(mut settings) => *settings = current,
+            None => {
+                commands.entity(entity).insert(current);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+    use crate::GaussianBlurSettings;
+
+    fn transition(
+        repeat: BlurTransitionRepeat,
+        duration: Duration,
+    ) -> BlurTransition<GaussianBlurSettings> {
+        BlurTransition::new(
+            GaussianBlurSettings::NO_BLUR,
+            GaussianBlurSettings::from_sigma(10.0),
+            duration,
+        )
+        .with_repeat(repeat)
+    }
+
+    #[test]
+    fn repeated_transition_finishes_after_its_runs() {
+        let second = Duration::from_secs(1);
+        let mut transition = transition(BlurTransitionRepeat::Times(3), second);
+        for _ in 0..2 {
+            transition.tick(second);
+            assert!(!transition.is_finished());
+            assert_eq!(transition.progress(), 0.0);
+        }
+        transition.tick(second / 2);
+        assert!(!transition.is_finished());
+        transition.tick(second);
+        assert!(transition.is_finished());
+        assert_eq!(transition.progress(), 1.0);
+    }
+
+    #[test]
+    fn forever_transition_never_finishes() {
+        let second = Duration::from_secs(1);
+        let mut transition = transition(BlurTransitionRepeat::Forever, second);
+        transition.tick(second * 1000 + second / 4);
+        assert!(!transition.is_finished());
+        assert_eq!(transition.progress(), 0.25);
+    }
+
+    #[test]
+    fn zero_duration_transition_does_not_loop() {
+        // Each tick completes a single run.
+        let mut repeated = transition(BlurTransitionRepeat::Times(3), Duration::ZERO);
+        for runs in 1..=3 {
+            assert!(!repeated.is_finished());
+            repeated.tick(Duration::from_secs(1));
+            assert_eq!(repeated.runs, runs);
+            assert_eq!(repeated.current(), repeated.to);
+        }
+        assert!(repeated.is_finished());
+
+        let mut forever = transition(BlurTransitionRepeat::Forever, Duration::ZERO);
+        forever.tick(Duration::from_secs(1));
+        assert_eq!(forever.runs, 1);
+        assert!(!forever.is_finished());
+    }
+
+    #[test]
+    fn skipped_transition_ends_on_its_target() {
+        for repeat in [
+            BlurTransitionRepeat::Never,
+            BlurTransitionRepeat::Times(4),
+            BlurTransitionRepeat::Forever,
+        ] {
+            let mut transition = transition(repeat, Duration::from_secs(1));
+            transition.skip_to_end();
+            assert_eq!(transition.current(), transition.to);
+        }
+    }
+
+    #[test]
+    fn finished_transition_removes_settings_without_blur() {
+        let mut app = App::new();
+        app.init_resource::<Time<Virtual>>()
+            .init_resource::<Time<Real>>()
+            .add_event::<BlurTransitionFinished>()
+            .add_systems(Update, animate_blur_transitions::<GaussianBlurSettings>);
+        let entity = app
+            .world
+            .spawn((
+                GaussianBlurSettings::from_sigma(10.0),
+                BlurTransition::new(
+                    GaussianBlurSettings::from_sigma(10.0),
+                    GaussianBlurSettings::NO_BLUR,
+                    Duration::ZERO,
+                )
+                .removing_when_no_blur(),
+            ))
+            .id();
+
+        let mut reader = ManualEventReader::<BlurTransitionFinished>::default();
+        let mut finished = Vec::new();
+        for _ in 0..3 {
+            app.update();
+            let events = app.world.resource::<Events<BlurTransitionFinished>>();
+            finished.extend(reader.read(events).map(|event| event.entity));
+        }
+        assert_eq!(finished, [entity]);
+        let entity = app.world.entity(entity);
+        assert!(!entity.contains::<GaussianBlurSettings>());
+        assert!(!entity.contains::<BlurTransition<GaussianBlurSettings>>());
+    }
+}
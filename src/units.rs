@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+/// Defines the unit of the distances of the blur settings of a camera, added to the Camera entity.
+///
+/// The settings are converted to physical pixels for each view when extracted,
+/// so that a blur keeps its size across screen resolutions, window scale factors or zoom levels.
+/// The whole blur is scaled: for example the kernel of a gaussian blur grows, its `sampling_distance_factor`
+/// growing instead beyond the highest kernel size, and a dual blur gets additional downsampling passes.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///pub fn setup(mut commands: Commands) {
+///    commands.spawn((
+///        Camera2dBundle::default(),
+///        GaussianBlurSettings::default(),
+///        // The blur is as big on a 4K screen as on a 1080p one.
+///        BlurUnits::ViewportHeightFraction {
+///            reference_height: 1080.0,
+///        },
+///    ));
+///}
+///```
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Component, Default)]
+pub enum BlurUnits {
+    /// The settings are in physical pixels, as rendered.
+    #[default]
+    PhysicalPixels,
+    /// The settings are in logical pixels, scaled by the scale factor of the window.
+    LogicalPixels,
+    /// The blur is a fraction of the height of the viewport,
+    /// the settings being in pixels of a viewport `reference_height` physical pixels high.
+    ViewportHeightFraction {
+        /// Height of the viewport, in physical pixels, for which the settings are in physical pixels.
+        reference_height: f32,
+    },
+    /// The settings are in world units, for 2D cameras with an orthographic projection,
+    /// so that the blur scales with `OrthographicProjection::scale`.
+    ///
+    /// Other projections use physical pixels.
+    WorldUnits,
+}
+
+impl BlurUnits {
+    // Number of physical pixels of the viewport of `camera` per unit.
+    // 2D cameras have an `OrthographicProjection`, 3D cameras a `Projection`.
+    pub(crate) fn physical_pixels_per_unit(
+        &self,
+        camera: &Camera,
+        orthographic_projection: Option<&OrthographicProjection>,
+        projection: Option<&Projection>,
+    ) -> f32 {
+        let projection = orthographic_projection.or(match projection {
+            Some(Projection::Orthographic(projection)) => Some(projection),
+            _ => None,
+        });
+        let viewport_height = camera.physical_viewport_size().map(|size| size.y as f32);
+        let pixels_per_unit = match self {
+            BlurUnits::PhysicalPixels => None,
+            BlurUnits::LogicalPixels => camera.target_scaling_factor(),
+            BlurUnits::ViewportHeightFraction { reference_height } => {
+                viewport_height.map(|height| height / reference_height)
+            }
+            // The area of the projection is the part of the world covered by the viewport.
+            BlurUnits::WorldUnits => projection
+                .zip(viewport_height)
+                .map(|(projection, height)| height / projection.area.height()),
+        };
+        pixels_per_unit
+            .filter(|pixels_per_unit| pixels_per_unit.is_finite() && *pixels_per_unit > 0.0)
+            .unwrap_or(1.0)
+    }
+}
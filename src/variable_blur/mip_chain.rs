@@ -0,0 +1,290 @@
+// Mip chain of the view target, that effects can sample at any level of detail
+// for a blur whose cost does not depend on its radius.
+//
+// An effect requests the mip chain of its views by adding a `MipChainRequired` component
+// to them when extracted, and renders it at the start of its node with `MipChain::render`.
+// It can then be sampled with the `variable_blur` function of the `bevy_camera_blur::variable_blur` WGSL module.
+// The levels are as big as the view target, only the viewport of the camera being downsampled.
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    render::{
+        camera::ExtractedCamera,
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
+            DynamicUniformBuffer, FilterMode, FragmentState, MultisampleState, Operations,
+            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+            RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+            SamplerDescriptor, ShaderStages, ShaderType, TextureDescriptor, TextureDimension,
+            TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::{BevyDefault, CachedTexture, TextureCache},
+        view::ViewTarget,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+use crate::{
+    bind_groups::{CachedBindGroups, ViewBindGroups},
+    viewport::viewport_uv_rect,
+};
+
+const MIP_CHAIN_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x50362a281a094381a7e58d51ca65ee59);
+
+// Highest number of levels, enough for a radius of 256 pixels.
+const MAX_MIP_LEVELS: u32 = 9;
+
+/// Prepares and renders the mip chain of the views with a [`MipChainRequired`] component.
+pub(crate) struct MipChainPlugin;
+
+impl Plugin for MipChainPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            MIP_CHAIN_SHADER_HANDLE,
+            "mip_chain.wgsl",
+            Shader::from_wgsl
+        );
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ViewBindGroups<MipChainPipeline>>()
+            .init_resource::<MipChainUniformBuffer>()
+            .add_systems(
+                Render,
+                (
+                    prepare_mip_chains.in_set(RenderSet::PrepareResources),
+                    prepare_mip_chain_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<MipChainPipeline>();
+    }
+}
+
+/// Marks a view whose mip chain must be prepared.
+#[derive(Component, Clone, Copy)]
+pub struct MipChainRequired;
+
+/// Mip chain of a view target, each level being half the size of the previous one,
+/// the first level having the size of the view target.
+#[derive(Component, Clone)]
+pub(crate) struct MipChain {
+    texture: CachedTexture,
+    // View of each single level, to render it.
+    levels: Vec<TextureView>,
+    // Offset of the uniforms of the view in the `MipChainUniformBuffer`.
+    uniform_offset: u32,
+}
+
+impl MipChain {
+    /// View of all the levels, to sample the mip chain.
+    pub(crate) fn view(&self) -> &TextureView {
+        &self.texture.default_view
+    }
+
+    /// Renders all the levels of the mip chain from `source`,
+    /// each level being the downsampled previous one.
+    pub(crate) fn render(
+        &self,
+        render_context: &mut RenderContext,
+        pipeline: &RenderPipeline,
+        bind_groups: &CachedBindGroups,
+        source: &TextureView,
+    ) {
+        let mut source = source;
+        for level in &self.levels {
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("mip_chain_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: level,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, bind_groups.get(source), &[self.uniform_offset]);
+            render_pass.draw(0..3, 0..1);
+            source = level;
+        }
+    }
+}
+
+// Uniforms of the downsampling passes of all the views, each view binding its own offset.
+#[derive(Resource, Default)]
+pub(crate) struct MipChainUniformBuffer(DynamicUniformBuffer<MipChainUniforms>);
+
+#[derive(ShaderType, Clone)]
+pub(crate) struct MipChainUniforms {
+    // UV rectangle of the viewport of the camera in the view target
+    viewport: Vec4,
+}
+
+// The views of the levels are kept across frames, so that the bind groups sampling them stay valid.
+fn prepare_mip_chains(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut uniform_buffer: ResMut<MipChainUniformBuffer>,
+    mut mip_chains: Local<EntityHashMap<MipChain>>,
+    views: Query<(Entity, &ViewTarget, &ExtractedCamera), With<MipChainRequired>>,
+) {
+    mip_chains.retain(|view, _| views.contains(*view));
+    uniform_buffer.0.clear();
+    for (entity, view_target, camera) in &views {
+        let size = view_target.main_texture().size();
+        let mip_level_count = (size.width.min(size.height).max(1).ilog2() + 1).min(MAX_MIP_LEVELS);
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("mip_chain_texture"),
+                size,
+                mip_level_count,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::bevy_default(),
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        let mut mip_chain = match mip_chains.get(&entity) {
+            Some(mip_chain) if mip_chain.texture.texture.id() == texture.texture.id() => {
+                mip_chain.clone()
+            }
+            _ => {
+                let levels = (0..mip_level_count)
+                    .map(|level| {
+                        texture.texture.create_view(&TextureViewDescriptor {
+                            label: Some("mip_chain_level_view"),
+                            base_mip_level: level,
+                            mip_level_count: Some(1),
+                            ..default()
+                        })
+                    })
+                    .collect();
+                let mip_chain = MipChain {
+                    texture,
+                    levels,
+                    uniform_offset: 0,
+                };
+                mip_chains.insert(entity, mip_chain.clone());
+                mip_chain
+            }
+        };
+        mip_chain.uniform_offset = uniform_buffer.0.push(&MipChainUniforms {
+            viewport: viewport_uv_rect(camera.viewport.as_ref(), camera.physical_target_size),
+        });
+        commands.entity(entity).insert(mip_chain);
+    }
+    uniform_buffer.0.write_buffer(&render_device, &render_queue);
+}
+
+#[derive(Resource)]
+pub(crate) struct MipChainPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pub(crate) pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for MipChainPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "mip_chain_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<MipChainUniforms>(true),
+                ),
+            ),
+        );
+
+        // Linear filtering averages the 4 pixels covered by each pixel of the next level.
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("mip_chain_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: MIP_CHAIN_SHADER_HANDLE,
+                        shader_defs: vec![],
+                        entry_point: "fragment_downsample".into(),
+                        targets: vec![Some(TextureFormat::bevy_default().into())],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
+
+// The first level samples one of the main textures of the view, and each other level the previous one.
+fn prepare_mip_chain_bind_groups(
+    render_device: Res<RenderDevice>,
+    mip_chain_pipeline: Res<MipChainPipeline>,
+    uniform_buffer: Res<MipChainUniformBuffer>,
+    mut bind_groups: ResMut<ViewBindGroups<MipChainPipeline>>,
+    views: Query<(Entity, &ViewTarget, &MipChain)>,
+) {
+    bind_groups.retain(|view| views.contains(view));
+    let (Some(buffer), Some(uniforms_binding)) =
+        (uniform_buffer.0.buffer(), uniform_buffer.0.binding())
+    else {
+        return;
+    };
+    for (view, view_target, mip_chain) in &views {
+        let mut sources = vec![
+            view_target.main_texture_view(),
+            view_target.main_texture_other_view(),
+        ];
+        sources.extend(&mip_chain.levels[..mip_chain.levels.len() - 1]);
+        bind_groups.update(view, &sources, Some(buffer.id()), |source| {
+            render_device.create_bind_group(
+                "mip_chain_bind_group",
+                &mip_chain_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    source,
+                    &mip_chain_pipeline.sampler,
+                    uniforms_binding.clone(),
+                )),
+            )
+        });
+    }
+}
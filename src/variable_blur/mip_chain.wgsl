@@ -0,0 +1,22 @@
+// This shader renders a level of the mip chain from the previous one
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct MipChainUniforms {
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    viewport: vec4<f32>,
+}
+@group(0) @binding(2) var<uniform> settings: MipChainUniforms;
+
+// Each pixel is at the corner of 4 pixels of the previous level, averaged by the linear filtering.
+// The levels are as big as the view target, the samples staying half a texel inside the viewport
+// so that the pixels beyond it are not averaged.
+@fragment
+fn fragment_downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let half_texel = 0.5 / vec2<f32>(textureDimensions(source_texture));
+    let min_uv = settings.viewport.xy + half_texel;
+    let uv = clamp(in.uv, min_uv, max(min_uv, settings.viewport.zw - half_texel));
+    return textureSample(source_texture, texture_sampler, uv);
+}
@@ -0,0 +1,130 @@
+mod mip_chain;
+mod pipeline;
+mod settings;
+
+pub use settings::VariableBlurSettings;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::{
+        core_2d::graph::{Core2d, Node2d},
+        core_3d::graph::{Core3d, Node3d},
+    },
+    prelude::*,
+    render::{
+        extract_component::UniformComponentPlugin,
+        render_graph::{RenderGraphApp, ViewNodeRunner},
+        Render, RenderApp, RenderSet,
+    },
+};
+use mip_chain::MipChainPlugin;
+use pipeline::*;
+
+use crate::{bind_groups::ViewBindGroups, extract::ExtractBlurSettingsPlugin};
+
+use self::settings::VariableBlurUniforms;
+
+const VARIABLE_BLUR_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0xef39de8fe96846cf90deb8f6372c75e7);
+const VARIABLE_BLUR_FUNCTIONS_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x17afc3f3e6334a5ebdf68c5d72f67320);
+
+/// This plugins adds support for a blur whose radius varies for each pixel to 2D or 3D cameras,
+/// for depth of field, tilt-shift or masked blur effects.
+///
+/// It must be used in conjonction with a [`VariableBlurSettings`] component added to the Camera entity,
+/// defining the radius of each pixel with a texture.
+///
+/// This implementation renders a mip chain of the view, each level being half the size of the previous one,
+/// then samples for each pixel the level matching its radius, so that the cost does not depend on the radius.
+///
+/// The sampling function is public, other shaders can import it to blur with their own radius function:
+/// ```wgsl
+/// #import bevy_camera_blur::variable_blur::variable_blur
+///
+/// let color = variable_blur(mip_chain, mip_sampler, uv, radius);
+/// ```
+/// `variable_blur_in_viewport` only samples a viewport rectangle of the mip chain,
+/// for the cameras sharing a render target with `Camera::viewport`.
+///
+/// ```
+///# use bevy::prelude::*;
+///# use bevy_camera_blur::*;
+///
+///fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///    commands.spawn((
+///        Camera2dBundle::default(),
+///        // Sharp at the center, blurry on the borders.
+///        VariableBlurSettings::new(asset_server.load("vignette.png"), 24.0),
+///    ));
+///}
+///```
+///
+/// See [`VariableBlurSettings`] for configurability.
+///
+pub struct VariableBlurPlugin;
+
+impl Plugin for VariableBlurPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            VARIABLE_BLUR_SHADER_HANDLE,
+            "variable_blur.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            VARIABLE_BLUR_FUNCTIONS_SHADER_HANDLE,
+            "variable_blur_functions.wgsl",
+            Shader::from_wgsl
+        );
+        app.register_type::<VariableBlurSettings>();
+
+        if !app.is_plugin_added::<MipChainPlugin>() {
+            app.add_plugins(MipChainPlugin);
+        }
+        app.add_plugins((
+            ExtractBlurSettingsPlugin::<VariableBlurSettings>::default(),
+            UniformComponentPlugin::<VariableBlurUniforms>::default(),
+        ));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ViewBindGroups<VariableBlurPipeline>>()
+            .add_systems(
+                Render,
+                (prepare_variable_blur_bind_groups.in_set(RenderSet::PrepareBindGroups),),
+            )
+            // Add variable blur to the 3d render graph;
+            .add_render_graph_node::<ViewNodeRunner<VariableBlurNode>>(Core3d, VariableBlurLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    VariableBlurLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            )
+            // Add variable blur to the 2d render graph
+            .add_render_graph_node::<ViewNodeRunner<VariableBlurNode>>(Core2d, VariableBlurLabel)
+            .add_render_graph_edges(
+                Core2d,
+                (
+                    Node2d::Tonemapping,
+                    VariableBlurLabel,
+                    Node2d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<VariableBlurPipeline>();
+    }
+}
@@ -0,0 +1,224 @@
+use super::mip_chain::{MipChain, MipChainPipeline};
+use super::settings::{VariableBlurSettings, VariableBlurUniforms};
+use super::VARIABLE_BLUR_SHADER_HANDLE;
+use crate::bind_groups::ViewBindGroups;
+use bevy::{
+    core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        camera::ExtractedCamera,
+        extract_component::{ComponentUniforms, DynamicUniformIndex},
+        render_asset::RenderAssets,
+        render_graph::{NodeRunError, RenderGraphContext, RenderLabel, ViewNode},
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
+            FilterMode, FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState,
+            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
+            SamplerBindingType, SamplerDescriptor, ShaderStages, TextureFormat, TextureSampleType,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::BevyDefault,
+        view::ViewTarget,
+    },
+};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub(crate) struct VariableBlurLabel;
+
+// The post process node used for the render graph
+#[derive(Default)]
+pub(crate) struct VariableBlurNode;
+
+// The ViewNode trait is required by the ViewNodeRunner
+impl ViewNode for VariableBlurNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ExtractedCamera,
+        // This make sure the node is only run on cameras with an extracted VariableBlurUniforms component,
+        // the offset of its uniforms in the buffer shared by all the views
+        &'static DynamicUniformIndex<VariableBlurUniforms>,
+        &'static MipChain,
+    );
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, camera, uniform_index, mip_chain): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let variable_blur_pipeline = world.resource::<VariableBlurPipeline>();
+        let mip_chain_pipeline = world.resource::<MipChainPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let bind_groups = world.resource::<ViewBindGroups<VariableBlurPipeline>>();
+        let mip_chain_bind_groups = world.resource::<ViewBindGroups<MipChainPipeline>>();
+
+        // The bind groups are only prepared once the radius texture is loaded.
+        let (
+            Some(bind_groups),
+            Some(mip_chain_bind_groups),
+            Some(pipeline),
+            Some(mip_chain_render_pipeline),
+        ) = (
+            bind_groups.get(graph.view_entity()),
+            mip_chain_bind_groups.get(graph.view_entity()),
+            pipeline_cache.get_render_pipeline(variable_blur_pipeline.pipeline_id),
+            pipeline_cache.get_render_pipeline(mip_chain_pipeline.pipeline_id),
+        )
+        else {
+            return Ok(());
+        };
+
+        render_context
+            .command_encoder()
+            .push_debug_group("variable_blur");
+
+        let post_process = view_target.post_process_write();
+
+        mip_chain.render(
+            render_context,
+            mip_chain_render_pipeline,
+            mip_chain_bind_groups,
+            post_process.source,
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("variable_blur_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                // We need to specify the post process destination view here
+                // to make sure we write to the appropriate texture.
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        // The other cameras sharing the render target keep their viewports.
+        if let Some(viewport) = &camera.viewport {
+            render_pass.set_camera_viewport(viewport);
+        }
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            bind_groups.get(mip_chain.view()),
+            &[uniform_index.index()],
+        );
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        render_context.command_encoder().pop_debug_group();
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct VariableBlurPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for VariableBlurPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "variable_blur_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<VariableBlurUniforms>(true),
+                ),
+            ),
+        );
+
+        // The mip chain is sampled with linear filtering between its levels, for a smooth radius variation.
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("variable_blur_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    // This will setup a fullscreen triangle for the vertex state
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: VARIABLE_BLUR_SHADER_HANDLE,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(TextureFormat::bevy_default().into())],
+                    }),
+                    // All of the following properties are not important for this effect so just use the default values.
+                    // This struct doesn't have the Default trait implemented because not all field can have a default value.
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
+
+// The single pass samples the mip chain of the view, its bind group also binds the radius texture.
+pub(crate) fn prepare_variable_blur_bind_groups(
+    render_device: Res<RenderDevice>,
+    variable_blur_pipeline: Res<VariableBlurPipeline>,
+    settings_uniforms: Res<ComponentUniforms<VariableBlurUniforms>>,
+    images: Res<RenderAssets<Image>>,
+    mut bind_groups: ResMut<ViewBindGroups<VariableBlurPipeline>>,
+    views: Query<(Entity, &VariableBlurSettings, &MipChain)>,
+) {
+    bind_groups.retain(|view| {
+        views
+            .get(view)
+            .is_ok_and(|(_, settings, _)| images.get(&settings.radius_texture).is_some())
+    });
+    let (Some(buffer), Some(settings_binding)) = (
+        settings_uniforms.uniforms().buffer(),
+        settings_uniforms.uniforms().binding(),
+    ) else {
+        return;
+    };
+    for (view, settings, mip_chain) in &views {
+        let Some(radius_texture) = images.get(&settings.radius_texture) else {
+            continue;
+        };
+        bind_groups.update_binding(
+            view,
+            &[mip_chain.view()],
+            &[&radius_texture.texture_view],
+            Some(buffer.id()),
+            |source| {
+                render_device.create_bind_group(
+                    "variable_blur_bind_group",
+                    &variable_blur_pipeline.layout,
+                    &BindGroupEntries::sequential((
+                        source,
+                        &radius_texture.texture_view,
+                        &variable_blur_pipeline.sampler,
+                        settings_binding.clone(),
+                    )),
+                )
+            },
+        );
+    }
+}
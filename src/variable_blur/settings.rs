@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+use super::mip_chain::MipChainRequired;
+use crate::{extract::ExtractBlurSettings, viewport::viewport_uv_rect, BlurQuality};
+
+/// Applies a blur whose radius varies for each pixel to a 2d or 3d camera in conjonction with the [`VariableBlurPlugin`].
+///
+/// This can be used for depth of field, tilt-shift or masked blur effects.
+///
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct VariableBlurSettings {
+    /// Texture defining the blur radius of each pixel in its red channel,
+    /// `0.0` keeping the pixel sharp and `1.0` blurring it with `max_radius`.
+    /// - It is stretched over the viewport and sampled with linear filtering,
+    ///   a low resolution texture is enough for smooth variations.
+    /// - The effect is skipped until the texture is loaded.
+    pub radius_texture: Handle<Image>,
+    /// Blur radius, in pixels, of the pixels whose radius texture value is `1.0`.
+    /// - It will be clamped to the range [0..256]
+    /// - A value of 0 correspond to no blur, and will entirely skip the post-processing effect.
+    /// - Defaults to 16.
+    ///
+    /// The computational cost does not depend on the radius.
+    pub max_radius: f32,
+}
+impl Default for VariableBlurSettings {
+    fn default() -> Self {
+        Self {
+            radius_texture: Handle::default(),
+            max_radius: 16.0,
+        }
+    }
+}
+impl VariableBlurSettings {
+    /// Creates the settings blurring each pixel with the radius defined by `radius_texture`,
+    /// up to `max_radius` pixels.
+    pub fn new(radius_texture: Handle<Image>, max_radius: f32) -> VariableBlurSettings {
+        VariableBlurSettings {
+            radius_texture,
+            max_radius,
+        }
+    }
+    /// Computes a new `VariableBlurSettings` where each attribute is legal as expected by the shader.
+    pub fn create_concrete(&self) -> VariableBlurSettings {
+        VariableBlurSettings {
+            radius_texture: self.radius_texture.clone(),
+            max_radius: self.max_radius.clamp(0.0, 256.0),
+        }
+    }
+}
+
+impl ExtractBlurSettings for VariableBlurSettings {
+    type Out = (VariableBlurSettings, VariableBlurUniforms, MipChainRequired);
+
+    fn scaled(&self, factor: f32) -> Self {
+        VariableBlurSettings {
+            max_radius: self.max_radius * factor,
+            ..self.clone()
+        }
+    }
+
+    // The cost does not depend on the settings, the quality is ignored.
+    fn map_quality(&self, _quality: BlurQuality) -> Self {
+        self.clone()
+    }
+
+    // The 4 samples of a level as big as the radius give about the blur of a sigma half the radius.
+    fn capped(&self, max_sigma: f32) -> Self {
+        VariableBlurSettings {
+            max_radius: self.max_radius.min(2.0 * max_sigma),
+            ..self.clone()
+        }
+    }
+
+    fn extract(&self, camera: &Camera) -> Option<Self::Out> {
+        let settings = self.create_concrete();
+        if settings.max_radius == 0.0 {
+            None
+        } else {
+            let uniforms = VariableBlurUniforms {
+                max_radius: settings.max_radius,
+                _webgl2_padding: Vec3::ZERO,
+                viewport: viewport_uv_rect(camera.viewport.as_ref(), camera.physical_target_size()),
+            };
+            Some((settings, uniforms, MipChainRequired))
+        }
+    }
+}
+
+/// The uniform struct extracted from [`VariableBlurSettings`] attached to a Camera.
+/// Will be available for use in the variable blur shader.
+#[derive(Component, ShaderType, Clone)]
+pub struct VariableBlurUniforms {
+    // Legalized max_radius
+    pub max_radius: f32,
+    // webgl2 requires 16B padding
+    pub _webgl2_padding: Vec3,
+    // UV rectangle of the viewport of the camera in the view target
+    pub viewport: Vec4,
+}
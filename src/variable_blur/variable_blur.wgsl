@@ -0,0 +1,28 @@
+// This shader computes the variable blur effect
+
+// The blur radius of each pixel is read from the radius texture,
+// then the mip chain of the view target is sampled at the matching level.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_camera_blur::variable_blur::variable_blur_in_viewport
+
+@group(0) @binding(0) var mip_chain: texture_2d<f32>;
+@group(0) @binding(1) var radius_texture: texture_2d<f32>;
+@group(0) @binding(2) var texture_sampler: sampler;
+struct VariableBlurUniforms {
+    max_radius: f32,
+    // WebGL2 structs must be 16 byte aligned.
+    _webgl2_padding: vec3<f32>,
+    // UV rectangle of the viewport in the view target, min in xy and max in zw.
+    // The UV coordinates of the pass are relative to it, the render pass being restricted to the viewport.
+    viewport: vec4<f32>,
+}
+@group(0) @binding(3) var<uniform> settings: VariableBlurUniforms;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // The radius texture is stretched over the viewport, and the mip chain has the size of the view target.
+    let radius = textureSample(radius_texture, texture_sampler, in.uv).r * settings.max_radius;
+    let uv = mix(settings.viewport.xy, settings.viewport.zw, in.uv);
+    return variable_blur_in_viewport(mip_chain, texture_sampler, uv, radius, settings.viewport);
+}
@@ -0,0 +1,38 @@
+// Functions to blur with a radius varying for each pixel, at a constant cost.
+//
+// They can be imported by other shaders with `#import bevy_camera_blur::variable_blur::variable_blur`.
+
+#define_import_path bevy_camera_blur::variable_blur
+
+// Samples the mip chain 'mip_chain' at 'uv', blurred with a radius of 'radius' pixels of its first level.
+//
+// 'mip_sampler' must use linear filtering, for the pixels and between the levels.
+// Each pixel of the level 'log2(radius)' is as big as the blur, 4 samples of the previous level,
+// on the diagonals, hide its blocks.
+// The cost is 4 texture samplings whatever the radius, a radius of 0 sampling the first level as is.
+fn variable_blur(mip_chain: texture_2d<f32>, mip_sampler: sampler, uv: vec2<f32>, radius: f32) -> vec4<f32> {
+    return variable_blur_in_viewport(mip_chain, mip_sampler, uv, radius, vec4(0.0, 0.0, 1.0, 1.0));
+}
+
+// Same as `variable_blur`, only sampling the pixels of the 'viewport' rectangle of the mip chain,
+// given as its min in xy and its max in zw, 'uv' being in the UV coordinates of the mip chain.
+fn variable_blur_in_viewport(
+    mip_chain: texture_2d<f32>,
+    mip_sampler: sampler,
+    uv: vec2<f32>,
+    radius: f32,
+    viewport: vec4<f32>,
+) -> vec4<f32> {
+    let max_level = f32(textureNumLevels(mip_chain) - 1u);
+    let level = clamp(log2(max(radius, 1.0)) - 1.0, 0.0, max_level);
+    let offset = 0.5 * radius / vec2<f32>(textureDimensions(mip_chain, 0));
+    // The samples stay half a texel of the coarser sampled level inside the viewport.
+    let half_texel = 0.5 / vec2<f32>(textureDimensions(mip_chain, u32(ceil(level))));
+    let min_uv = viewport.xy + half_texel;
+    let max_uv = max(min_uv, viewport.zw - half_texel);
+    var color = textureSampleLevel(mip_chain, mip_sampler, clamp(uv + vec2(offset.x, offset.y), min_uv, max_uv), level);
+    color += textureSampleLevel(mip_chain, mip_sampler, clamp(uv + vec2(-offset.x, offset.y), min_uv, max_uv), level);
+    color += textureSampleLevel(mip_chain, mip_sampler, clamp(uv + vec2(offset.x, -offset.y), min_uv, max_uv), level);
+    color += textureSampleLevel(mip_chain, mip_sampler, clamp(uv + vec2(-offset.x, -offset.y), min_uv, max_uv), level);
+    return color * 0.25;
+}
@@ -0,0 +1,42 @@
+use bevy::{prelude::*, render::camera::Viewport};
+
+// Rectangle of the viewport of a camera in the UV coordinates of its view target, as `(min.x, min.y, max.x, max.y)`.
+// The shaders only sample the pixels of this rectangle, so that the cameras sharing a render target
+// with `Camera::viewport`, as in split-screen, do not blur each other's view.
+// It is the whole view target when the camera has no viewport.
+pub(crate) fn viewport_uv_rect(viewport: Option<&Viewport>, target_size: Option<UVec2>) -> Vec4 {
+    match (viewport, target_size) {
+        (Some(viewport), Some(target_size)) if target_size.cmpgt(UVec2::ZERO).all() => {
+            let target_size = target_size.as_vec2();
+            let min = viewport.physical_position.as_vec2() / target_size;
+            let max = (viewport.physical_position + viewport.physical_size).as_vec2() / target_size;
+            min.min(Vec2::ONE)
+                .extend(max.x.min(1.0))
+                .extend(max.y.min(1.0))
+        }
+        _ => Vec4::new(0.0, 0.0, 1.0, 1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_screen_viewport_rect() {
+        let right_half = Viewport {
+            physical_position: UVec2::new(960, 0),
+            physical_size: UVec2::new(960, 1080),
+            ..default()
+        };
+        let target_size = Some(UVec2::new(1920, 1080));
+        assert_eq!(
+            viewport_uv_rect(Some(&right_half), target_size),
+            Vec4::new(0.5, 0.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            viewport_uv_rect(None, target_size),
+            Vec4::new(0.0, 0.0, 1.0, 1.0)
+        );
+    }
+}